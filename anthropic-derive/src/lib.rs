@@ -0,0 +1,323 @@
+//! # Anthropic Rust SDK derive macros
+//! Derive macros generating JSON Schema tool definitions from Rust types.
+//! They are re-exported by the `anthropic` crate with its `derive` feature, see
+//! `anthropic::tools::ToolInput` and `anthropic::tools::InputSchema`.
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::meta::ParseNestedMeta;
+use syn::{
+    Attribute, Data, DeriveInput, Error, Expr, Fields, Generics, Lit, LitStr, Meta, WherePredicate, parse_macro_input,
+    parse_quote,
+};
+
+/// Derive `anthropic::tools::InputSchema` for a struct with named fields.
+#[proc_macro_derive(InputSchema, attributes(tool))]
+pub fn derive_input_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let expanded = match input.attrs.iter().find(|attr| attr.path().is_ident("tool")) {
+        Some(attr) => Err(Error::new_spanned(attr, "`tool` attributes are only supported by `#[derive(ToolInput)]`")),
+        None => input_schema_impl(&input),
+    };
+    expanded.unwrap_or_else(Error::into_compile_error).into()
+}
+
+/// Derive `anthropic::tools::ToolInput` and `anthropic::tools::InputSchema` for a struct with named
+/// fields. The struct must also implement `serde::Deserialize`.
+#[proc_macro_derive(ToolInput, attributes(tool))]
+pub fn derive_tool_input(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let expanded = input_schema_impl(&input).and_then(|schema| {
+        let tool = tool_input_impl(&input)?;
+        Ok(quote! { #schema #tool })
+    });
+    expanded.unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn input_schema_impl(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(Error::new_spanned(input, "only structs with named fields are supported")),
+        },
+        _ => return Err(Error::new_spanned(input, "only structs with named fields are supported")),
+    };
+
+    let container = ContainerAttrs::parse(&input.attrs)?;
+    let mut properties = Vec::new();
+    for field in fields {
+        let attrs = SerdeAttrs::parse(&field.attrs)?;
+        if attrs.skip {
+            continue;
+        }
+        let name = match attrs.rename {
+            Some(name) => name,
+            None => container.rename_all.apply(&field.ident.as_ref().unwrap().to_string()),
+        };
+        let description = match doc_comment(&field.attrs) {
+            Some(doc) => quote! { ::core::option::Option::Some(#doc) },
+            None => quote! { ::core::option::Option::None },
+        };
+        let ty = &field.ty;
+        let optional = container.default || attrs.default;
+        properties.push(quote! { .property::<#ty>(#name, #description, #optional) });
+    }
+
+    let ident = &input.ident;
+    let generics = bounded(&input.generics, quote!(::anthropic::tools::InputSchema));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::anthropic::tools::InputSchema for #ident #ty_generics #where_clause {
+            fn input_schema() -> ::anthropic::__private::serde_json::Value {
+                ::anthropic::tools::ObjectSchema::new()
+                    #(#properties)*
+                    .build()
+            }
+        }
+    })
+}
+
+fn tool_input_impl(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let name = match tool_name(&input.attrs)? {
+        Some(name) => name.value(),
+        None => snake_case(&input.ident.to_string()),
+    };
+    let description = match doc_comment(&input.attrs) {
+        Some(doc) => quote! { ::core::option::Option::Some(#doc.to_string()) },
+        None => quote! { ::core::option::Option::None },
+    };
+
+    let ident = &input.ident;
+    let bound = quote!(::anthropic::tools::InputSchema + ::anthropic::__private::serde::de::DeserializeOwned);
+    let generics = bounded(&input.generics, bound);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::anthropic::tools::ToolInput for #ident #ty_generics #where_clause {
+            fn name() -> ::std::string::String {
+                #name.to_string()
+            }
+
+            fn description() -> ::core::option::Option<::std::string::String> {
+                #description
+            }
+        }
+    })
+}
+
+/// Require `bound` from every type parameter, for the properties of that type to have a schema.
+fn bounded(generics: &Generics, bound: TokenStream2) -> Generics {
+    let mut generics = generics.clone();
+    let params: Vec<_> = generics.type_params().map(|param| param.ident.clone()).collect();
+    let where_clause = generics.make_where_clause();
+    for param in params {
+        let predicate: WherePredicate = parse_quote!(#param: #bound);
+        where_clause.predicates.push(predicate);
+    }
+    generics
+}
+
+/// Parse `#[tool(name = "...")]`.
+fn tool_name(attrs: &[Attribute]) -> Result<Option<LitStr>, Error> {
+    let mut name = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("tool")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                name = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported tool attribute, expected `name`"))
+            }
+        })?;
+    }
+    Ok(name)
+}
+
+/// The `serde` container attributes changing the schema of a struct.
+#[derive(Default)]
+struct ContainerAttrs {
+    rename_all: RenameRule,
+    default: bool,
+}
+
+impl ContainerAttrs {
+    fn parse(attrs: &[Attribute]) -> Result<Self, Error> {
+        let mut parsed = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename_all") {
+                    if let Some(rule) = deserialize_name(&meta)? {
+                        parsed.rename_all = RenameRule::parse(&rule)?;
+                    }
+                } else if meta.path.is_ident("default") {
+                    parsed.default = true;
+                    if meta.input.peek(syn::Token![=]) {
+                        meta.value()?.parse::<LitStr>()?;
+                    }
+                } else if ["tag", "content", "untagged", "rename_all_fields", "transparent", "from", "try_from"]
+                    .iter()
+                    .any(|unsupported| meta.path.is_ident(unsupported))
+                {
+                    return Err(meta.error("unsupported serde attribute, the schema cannot be derived"));
+                } else {
+                    skip_meta(&meta)?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(parsed)
+    }
+}
+
+/// The `serde(rename_all = "...")` conversion of the field names.
+#[derive(Default, Clone, Copy)]
+enum RenameRule {
+    #[default]
+    None,
+    Upper,
+    Pascal,
+    Camel,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn parse(rule: &LitStr) -> Result<Self, Error> {
+        match rule.value().as_str() {
+            "lowercase" | "snake_case" => Ok(Self::None),
+            "UPPERCASE" => Ok(Self::Upper),
+            "PascalCase" => Ok(Self::Pascal),
+            "camelCase" => Ok(Self::Camel),
+            "SCREAMING_SNAKE_CASE" => Ok(Self::ScreamingSnake),
+            "kebab-case" => Ok(Self::Kebab),
+            "SCREAMING-KEBAB-CASE" => Ok(Self::ScreamingKebab),
+            _ => Err(Error::new_spanned(rule, "unknown rename rule")),
+        }
+    }
+
+    /// Convert the snake_case name of a field, as serde does.
+    fn apply(self, field: &str) -> String {
+        match self {
+            Self::None => field.to_string(),
+            Self::Upper | Self::ScreamingSnake => field.to_ascii_uppercase(),
+            Self::Pascal => field
+                .split('_')
+                .map(|word| {
+                    let mut chars = word.chars();
+                    chars
+                        .next()
+                        .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                        .unwrap_or_default()
+                })
+                .collect(),
+            Self::Camel => {
+                let pascal = Self::Pascal.apply(field);
+                let mut chars = pascal.chars();
+                chars.next().map(|first| first.to_ascii_lowercase().to_string() + chars.as_str()).unwrap_or_default()
+            }
+            Self::Kebab => field.replace('_', "-"),
+            Self::ScreamingKebab => field.replace('_', "-").to_ascii_uppercase(),
+        }
+    }
+}
+
+/// Parse the name of `rename = "..."` or `rename(deserialize = "...")`, ignoring the name used
+/// only for serialization.
+fn deserialize_name(meta: &ParseNestedMeta) -> Result<Option<LitStr>, Error> {
+    if meta.input.peek(syn::Token![=]) {
+        return Ok(Some(meta.value()?.parse()?));
+    }
+    let mut name = None;
+    meta.parse_nested_meta(|meta| {
+        let value = meta.value()?.parse()?;
+        if meta.path.is_ident("deserialize") {
+            name = Some(value);
+        }
+        Ok(())
+    })?;
+    Ok(name)
+}
+
+/// Skip the value of an attribute not changing the schema.
+fn skip_meta(meta: &ParseNestedMeta) -> Result<(), Error> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(|meta| skip_meta(&meta))?;
+    }
+    Ok(())
+}
+
+/// The `serde` field attributes changing the schema of a field.
+#[derive(Default)]
+struct SerdeAttrs {
+    rename: Option<String>,
+    default: bool,
+    skip: bool,
+}
+
+impl SerdeAttrs {
+    fn parse(attrs: &[Attribute]) -> Result<Self, Error> {
+        let mut parsed = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    if let Some(name) = deserialize_name(&meta)? {
+                        parsed.rename = Some(name.value());
+                    }
+                } else if meta.path.is_ident("default") {
+                    parsed.default = true;
+                    if meta.input.peek(syn::Token![=]) {
+                        meta.value()?.parse::<LitStr>()?;
+                    }
+                } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_deserializing") {
+                    parsed.skip = true;
+                } else if meta.path.is_ident("flatten") {
+                    return Err(meta.error("unsupported serde attribute, the schema cannot be derived"));
+                } else {
+                    // Other attributes do not change the schema.
+                    skip_meta(&meta)?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(parsed)
+    }
+}
+
+/// Join the lines of the doc comments of an item.
+fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(meta) if meta.path.is_ident("doc") => match &meta.value {
+                Expr::Lit(expr) => match &expr.lit {
+                    Lit::Str(doc) => Some(doc.value().trim().to_string()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    let doc = lines.join("\n").trim().to_string();
+    (!doc.is_empty()).then_some(doc)
+}
+
+/// Convert a type name to snake_case, keeping runs of capitals as one word: `HTTPRequest` becomes
+/// `http_request`.
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let previous = chars[i - 1];
+            let next_is_lowercase = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if previous.is_lowercase() || previous.is_ascii_digit() || (previous.is_uppercase() && next_is_lowercase) {
+                snake.push('_');
+            }
+        }
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}
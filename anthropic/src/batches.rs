@@ -0,0 +1,441 @@
+//! Message Batches API, processing many message requests asynchronously.
+//! Ref: https://docs.anthropic.com/en/api/creating-message-batches
+use std::collections::HashMap;
+use std::fmt;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::Method;
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Deserializer, Serialize};
+use tokio::time::Instant;
+use tokio_stream::{Stream, StreamExt};
+
+use crate::client::{Client, request_headers};
+use crate::error::{AnthropicError, ApiError, WrappedError, map_deserialization_error};
+use crate::pagination::{ListParams, Page, PageStream};
+use crate::response::Response;
+use crate::transport::{ByteStream, HttpResponse};
+use crate::types::{CreateMessageRequest, CreateMessageResponse, KnownTypes, UnknownType, unknown_type};
+
+/// The path of the Message Batches API.
+const BATCHES_PATH: &str = "/v1/messages/batches";
+/// Default interval between two polls of a batch by [Client::wait_for_batch].
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A message request of a batch.
+#[derive(Clone, Serialize, Debug, PartialEq)]
+pub struct MessageBatchRequest {
+    /// The id matching the request with its result, unique within the batch.
+    pub custom_id: String,
+    /// The message request. Streaming and extra headers are not supported in batches. The betas
+    /// of the requests are enabled for the whole batch, and their extra body fields are sent.
+    pub params: CreateMessageRequest,
+}
+
+impl MessageBatchRequest {
+    /// Create a batch request from a message request.
+    pub fn new(custom_id: impl Into<String>, params: CreateMessageRequest) -> Self {
+        Self { custom_id: custom_id.into(), params }
+    }
+}
+
+/// A request creating a message batch.
+#[derive(Clone, Serialize, Default, Debug, Builder, PartialEq)]
+#[builder(pattern = "mutable")]
+#[builder(setter(into, strip_option), default)]
+#[builder(derive(Debug))]
+#[builder(build_fn(error = "AnthropicError"))]
+pub struct CreateMessageBatchRequest {
+    /// The message requests of the batch.
+    pub requests: Vec<MessageBatchRequest>,
+    /// The `idempotency-key` header of the creation of the batch, for the API to not create it
+    /// twice when it is retried.
+    #[serde(skip)]
+    pub idempotency_key: Option<String>,
+}
+
+impl CreateMessageBatchRequest {
+    /// Match the results of the batch with its requests, by `custom_id`, as the results are not in
+    /// the order of the requests.
+    /// # Arguments
+    /// * `results` - The results of the batch created from this request.
+    /// # Returns
+    /// Each request with its result, in the order of the requests. The result is `None` when the
+    /// results have no entry for the request.
+    /// # Errors
+    /// * `AnthropicError` - If the results cannot be received.
+    pub async fn join_results(
+        &self,
+        mut results: MessageBatchResultStream,
+    ) -> Result<Vec<(&MessageBatchRequest, Option<MessageBatchResult>)>, AnthropicError> {
+        let mut by_custom_id = HashMap::new();
+        while let Some(response) = results.next().await {
+            let response = response?;
+            by_custom_id.insert(response.custom_id, response.result);
+        }
+        Ok(self.requests.iter().map(|request| (request, by_custom_id.remove(&request.custom_id))).collect())
+    }
+}
+
+impl From<Vec<MessageBatchRequest>> for CreateMessageBatchRequest {
+    fn from(requests: Vec<MessageBatchRequest>) -> Self {
+        Self { requests, idempotency_key: None }
+    }
+}
+
+/// A batch of message requests.
+#[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
+pub struct MessageBatch {
+    /// The id of the batch.
+    pub id: String,
+    /// Whether the batch is still processed.
+    pub processing_status: ProcessingStatus,
+    /// The number of requests of the batch in each state.
+    pub request_counts: RequestCounts,
+    /// When the processing ended.
+    pub ended_at: Option<DateTime<Utc>>,
+    /// When the batch was created.
+    pub created_at: DateTime<Utc>,
+    /// When the batch expires if its processing has not ended.
+    pub expires_at: DateTime<Utc>,
+    /// When the batch was archived and its results became unavailable.
+    pub archived_at: Option<DateTime<Utc>>,
+    /// When the cancellation of the batch was initiated.
+    pub cancel_initiated_at: Option<DateTime<Utc>>,
+    /// The URL of the results file, once the processing ended.
+    pub results_url: Option<String>,
+}
+
+/// The processing status of a [MessageBatch].
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessingStatus {
+    /// Requests are being processed.
+    InProgress,
+    /// The batch is being canceled.
+    Canceling,
+    /// All the requests have a result.
+    Ended,
+    /// A processing status unknown to this version of the SDK.
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+/// The number of requests of a [MessageBatch] in each state.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RequestCounts {
+    /// Requests still being processed.
+    pub processing: u32,
+    /// Requests completed successfully.
+    pub succeeded: u32,
+    /// Requests that failed.
+    pub errored: u32,
+    /// Requests canceled before being processed.
+    pub canceled: u32,
+    /// Requests not processed before the batch expired.
+    pub expired: u32,
+}
+
+/// The confirmation of a batch deletion.
+#[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
+pub struct DeletedMessageBatch {
+    /// The id of the deleted batch.
+    pub id: String,
+}
+
+/// The result of a message request of a batch, read from the results file.
+#[derive(Debug, Deserialize)]
+pub struct MessageBatchIndividualResponse {
+    /// The id of the request, as given in its [MessageBatchRequest].
+    pub custom_id: String,
+    /// The result of the request.
+    pub result: MessageBatchResult,
+}
+
+/// The result of a message request of a batch.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MessageBatchResult {
+    /// The request succeeded with this message.
+    Succeeded { message: CreateMessageResponse },
+    /// The request failed with this error.
+    Errored {
+        #[serde(deserialize_with = "unwrap_error")]
+        error: ApiError,
+    },
+    /// The batch was canceled before the request was processed.
+    Canceled,
+    /// The batch expired before the request was processed.
+    Expired,
+    /// A result unknown to this version of the SDK.
+    #[serde(untagged, deserialize_with = "unknown_type::<_, MessageBatchResult>")]
+    Unknown(UnknownType),
+}
+
+impl KnownTypes for MessageBatchResult {
+    const TYPES: &'static [&'static str] = &["succeeded", "errored", "canceled", "expired"];
+}
+
+/// Errors of the results file are wrapped in an error object like API errors.
+fn unwrap_error<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ApiError, D::Error> {
+    Ok(WrappedError::deserialize(deserializer)?.error)
+}
+
+/// How [Client::wait_for_batch] polls a batch.
+#[derive(Clone)]
+pub struct WaitForBatchOptions {
+    poll_interval: Duration,
+    timeout: Option<Duration>,
+    on_progress: Option<ProgressCallback>,
+}
+
+type ProgressCallback = Arc<dyn Fn(&MessageBatch) + Send + Sync>;
+
+impl WaitForBatchOptions {
+    /// Poll every [DEFAULT_POLL_INTERVAL], without timeout nor progress callback.
+    pub fn new() -> Self {
+        Self { poll_interval: DEFAULT_POLL_INTERVAL, timeout: None, on_progress: None }
+    }
+
+    /// Set the interval between two polls.
+    pub fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Fail with [AnthropicError::BatchTimeout] if the processing has not ended after `timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Call `on_progress` with the batch after each poll, e.g. to report its
+    /// [request counts](MessageBatch::request_counts).
+    pub fn on_progress<F>(mut self, on_progress: F) -> Self
+    where
+        F: Fn(&MessageBatch) + Send + Sync + 'static,
+    {
+        self.on_progress = Some(Arc::new(on_progress));
+        self
+    }
+}
+
+impl Default for WaitForBatchOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for WaitForBatchOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WaitForBatchOptions")
+            .field("poll_interval", &self.poll_interval)
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Parsed results file of a batch, one result per request.
+pub type MessageBatchResultStream =
+    Pin<Box<dyn Stream<Item = Result<MessageBatchIndividualResponse, AnthropicError>> + Send>>;
+
+impl Client {
+    /// Create a message batch.
+    /// # Arguments
+    /// * `request` - The message requests of the batch, each with a custom id.
+    /// # Returns
+    /// The created batch.
+    /// # Errors
+    /// * `AnthropicError::InvalidArgument` - If a request is streamed, has extra headers or an
+    ///   idempotency key, or if the idempotency key of the batch is not a valid header value.
+    /// * `AnthropicError` - If the request fails.
+    pub async fn create_message_batch(
+        &self,
+        request: impl Into<CreateMessageBatchRequest>,
+    ) -> Result<MessageBatch, AnthropicError> {
+        let mut request = request.into();
+        if request.requests.iter().any(|request| request.params.stream) {
+            return Err(AnthropicError::InvalidArgument("Batch requests cannot be streamed".into()));
+        }
+        if request.requests.iter().any(|request| !request.params.extra_headers.is_empty()) {
+            return Err(AnthropicError::InvalidArgument(
+                "Batch requests cannot have extra headers, use Client::with_options() instead".into(),
+            ));
+        }
+        if request.requests.iter().any(|request| request.params.idempotency_key.is_some()) {
+            return Err(AnthropicError::InvalidArgument(
+                "Batch requests cannot have an idempotency key, set the one of the batch instead".into(),
+            ));
+        }
+        let mut betas = Vec::new();
+        for request in &mut request.requests {
+            self.default_model(&mut request.params.model);
+            betas.extend(request.params.betas.iter().cloned());
+        }
+        // The betas are sent with the creation of the batch, for all its requests.
+        let headers = request_headers(&HeaderMap::new(), request.idempotency_key.as_deref())?;
+        self.with_headers(&betas, &headers).post(BATCHES_PATH, request).await.map(Response::into_data)
+    }
+
+    /// Retrieve a message batch, to follow its processing.
+    /// # Arguments
+    /// * `batch_id` - The id of the batch.
+    /// # Returns
+    /// The batch.
+    /// # Errors
+    /// * `AnthropicError` - If the request fails.
+    pub async fn get_message_batch(&self, batch_id: &str) -> Result<MessageBatch, AnthropicError> {
+        self.get(&format!("{BATCHES_PATH}/{batch_id}"), &()).await.map(Response::into_data)
+    }
+
+    /// List a page of the message batches of the workspace, most recent first.
+    /// # Arguments
+    /// * `params` - The pagination parameters.
+    /// # Returns
+    /// The page of batches.
+    /// # Errors
+    /// * `AnthropicError` - If the request fails.
+    pub async fn list_message_batches(&self, params: &ListParams) -> Result<Page<MessageBatch>, AnthropicError> {
+        self.get(BATCHES_PATH, params).await.map(Response::into_data)
+    }
+
+    /// List all the message batches of the workspace, most recent first, fetching the pages as
+    /// the stream is consumed.
+    /// # Arguments
+    /// * `params` - The parameters of the first page.
+    /// # Returns
+    /// A stream of the batches.
+    pub fn list_all_message_batches(&self, params: ListParams) -> PageStream<MessageBatch> {
+        self.paginate(BATCHES_PATH, params)
+    }
+
+    /// Cancel a message batch. The requests not processed yet get a `canceled` result.
+    /// # Arguments
+    /// * `batch_id` - The id of the batch.
+    /// # Returns
+    /// The batch, `canceling` until the processing ends.
+    /// # Errors
+    /// * `AnthropicError` - If the request fails.
+    pub async fn cancel_message_batch(&self, batch_id: &str) -> Result<MessageBatch, AnthropicError> {
+        let request = self.request(Method::POST, &format!("{BATCHES_PATH}/{batch_id}/cancel"))?.build()?;
+        self.execute(request).await.map(Response::into_data)
+    }
+
+    /// Delete a message batch whose processing ended.
+    /// # Arguments
+    /// * `batch_id` - The id of the batch.
+    /// # Returns
+    /// The confirmation of the deletion.
+    /// # Errors
+    /// * `AnthropicError` - If the request fails.
+    pub async fn delete_message_batch(&self, batch_id: &str) -> Result<DeletedMessageBatch, AnthropicError> {
+        self.delete(&format!("{BATCHES_PATH}/{batch_id}")).await.map(Response::into_data)
+    }
+
+    /// Download the results file of a message batch whose processing ended, and parse it as it is
+    /// received. Results are not in the order of the requests, match them with their `custom_id`,
+    /// e.g. with [CreateMessageBatchRequest::join_results].
+    /// # Arguments
+    /// * `batch_id` - The id of the batch.
+    /// # Returns
+    /// A stream of the result of each request.
+    /// # Errors
+    /// * `AnthropicError` - If the request fails.
+    pub async fn message_batch_results(&self, batch_id: &str) -> Result<MessageBatchResultStream, AnthropicError> {
+        let request = self.request(Method::GET, &format!("{BATCHES_PATH}/{batch_id}/results"))?.build()?;
+        let (response, _) = self.send(request).await?;
+
+        Ok(jsonl_stream(response))
+    }
+
+    /// Poll a message batch until its processing ends, then stream its results.
+    /// # Arguments
+    /// * `batch_id` - The id of the batch.
+    /// * `options` - The poll interval, timeout and progress callback.
+    /// # Returns
+    /// A stream of the result of each request, to match with the requests by `custom_id`.
+    /// # Errors
+    /// * `AnthropicError::BatchTimeout` - If the processing has not ended before the timeout.
+    /// * `AnthropicError` - If a request fails.
+    pub async fn wait_for_batch(
+        &self,
+        batch_id: &str,
+        options: WaitForBatchOptions,
+    ) -> Result<MessageBatchResultStream, AnthropicError> {
+        let started = Instant::now();
+        loop {
+            let batch = self.get_message_batch(batch_id).await?;
+            if let Some(on_progress) = &options.on_progress {
+                on_progress(&batch);
+            }
+            if batch.processing_status == ProcessingStatus::Ended {
+                return self.message_batch_results(batch_id).await;
+            }
+
+            let elapsed = started.elapsed();
+            let wait = match options.timeout {
+                Some(timeout) if elapsed >= timeout => {
+                    return Err(AnthropicError::BatchTimeout { batch_id: batch_id.to_string(), timeout });
+                }
+                // Poll one last time when the timeout expires.
+                Some(timeout) => options.poll_interval.min(timeout - elapsed),
+                None => options.poll_interval,
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// The state of a JSON Lines stream between two items.
+struct JsonLines {
+    body: ByteStream,
+    /// The bytes received after the last complete line.
+    buffer: Vec<u8>,
+    /// Whether the body has been fully received, or failed.
+    done: bool,
+}
+
+/// Parse a JSON Lines response body as it is received.
+fn jsonl_stream<O>(response: HttpResponse) -> Pin<Box<dyn Stream<Item = Result<O, AnthropicError>> + Send>>
+where
+    O: for<'de> Deserialize<'de> + Send + 'static,
+{
+    let lines = JsonLines { body: response.body, buffer: Vec::new(), done: false };
+
+    Box::pin(futures_util::stream::unfold(lines, |mut lines| async move {
+        loop {
+            if let Some(end) = lines.buffer.iter().position(|byte| *byte == b'\n') {
+                let line: Vec<u8> = lines.buffer.drain(..=end).collect();
+                match parse_line(&line) {
+                    Some(item) => return Some((item, lines)),
+                    None => continue,
+                }
+            }
+            if lines.done {
+                // The last line may not end with a newline.
+                let line = std::mem::take(&mut lines.buffer);
+                return parse_line(&line).map(|item| (item, lines));
+            }
+            match lines.body.next().await {
+                Some(Ok(chunk)) => lines.buffer.extend_from_slice(&chunk),
+                Some(Err(e)) => {
+                    lines.done = true;
+                    lines.buffer.clear();
+                    return Some((Err(e), lines));
+                }
+                None => lines.done = true,
+            }
+        }
+    }))
+}
+
+fn parse_line<O: for<'de> Deserialize<'de>>(line: &[u8]) -> Option<Result<O, AnthropicError>> {
+    let line = line.trim_ascii();
+    if line.is_empty() {
+        return None;
+    }
+    Some(serde_json::from_slice(line).map_err(|e| map_deserialization_error(e, line)))
+}
@@ -0,0 +1,96 @@
+//! Beta features, enabled with the `anthropic-beta` header.
+//! Ref: https://docs.anthropic.com/en/api/beta-headers
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// A beta feature. Use [Beta::Other] for features unknown to this version of the SDK.
+///
+/// Enabled for all the requests of a client with `ClientBuilder::beta`, or for a single request
+/// with the `betas` of the request.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum Beta {
+    /// `files-api-2025-04-14`, required by the Files API and the requests referencing files.
+    FilesApi,
+    /// `interleaved-thinking-2025-05-14`
+    InterleavedThinking,
+    /// `fine-grained-tool-streaming-2025-05-14`
+    FineGrainedToolStreaming,
+    /// `token-efficient-tools-2025-02-19`
+    TokenEfficientTools,
+    /// `output-128k-2025-02-19`
+    Output128k,
+    /// `context-1m-2025-08-07`
+    Context1m,
+    /// `context-management-2025-06-27`
+    ContextManagement,
+    /// `code-execution-2025-08-25`
+    CodeExecution,
+    /// `mcp-client-2025-04-04`
+    McpClient,
+    /// `computer-use-2025-01-24`
+    ComputerUse,
+    /// Any other beta feature.
+    Other(String),
+}
+
+impl Beta {
+    /// The known beta features.
+    pub const KNOWN: [Beta; 10] = [
+        Beta::FilesApi,
+        Beta::InterleavedThinking,
+        Beta::FineGrainedToolStreaming,
+        Beta::TokenEfficientTools,
+        Beta::Output128k,
+        Beta::Context1m,
+        Beta::ContextManagement,
+        Beta::CodeExecution,
+        Beta::McpClient,
+        Beta::ComputerUse,
+    ];
+
+    /// The name of the feature, as sent in the `anthropic-beta` header.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Beta::FilesApi => "files-api-2025-04-14",
+            Beta::InterleavedThinking => "interleaved-thinking-2025-05-14",
+            Beta::FineGrainedToolStreaming => "fine-grained-tool-streaming-2025-05-14",
+            Beta::TokenEfficientTools => "token-efficient-tools-2025-02-19",
+            Beta::Output128k => "output-128k-2025-02-19",
+            Beta::Context1m => "context-1m-2025-08-07",
+            Beta::ContextManagement => "context-management-2025-06-27",
+            Beta::CodeExecution => "code-execution-2025-08-25",
+            Beta::McpClient => "mcp-client-2025-04-04",
+            Beta::ComputerUse => "computer-use-2025-01-24",
+            Beta::Other(name) => name,
+        }
+    }
+}
+
+impl fmt::Display for Beta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<String> for Beta {
+    fn from(name: String) -> Self {
+        Beta::KNOWN.into_iter().find(|beta| beta.as_str() == name).unwrap_or(Beta::Other(name))
+    }
+}
+
+impl From<&str> for Beta {
+    fn from(name: &str) -> Self {
+        Beta::from(name.to_string())
+    }
+}
+
+impl From<Beta> for String {
+    fn from(beta: Beta) -> Self {
+        match beta {
+            Beta::Other(name) => name,
+            beta => beta.as_str().to_string(),
+        }
+    }
+}
@@ -1,53 +1,157 @@
 use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use reqwest::header::{HeaderMap, ACCEPT, CONTENT_TYPE};
-use reqwest_eventsource::{Event, EventSource, RequestBuilderExt};
-use serde::de::DeserializeOwned;
+use backoff::backoff::Backoff;
+use reqwest::Method;
+use reqwest::header::{ACCEPT, CONTENT_TYPE, HeaderMap, HeaderValue};
 use serde::Serialize;
+use serde::de::DeserializeOwned;
 use tokio_stream::{Stream, StreamExt};
 
+use crate::beta::Beta;
 use crate::config::AnthropicConfig;
-use crate::error::{map_deserialization_error, AnthropicError, WrappedError};
-use crate::types::{CompleteRequest, CompleteResponse, CompleteResponseStream, CreateMessageRequest, CreateMessageResponse, CreateMessageResponseStream};
+use crate::error::{AnthropicError, WrappedError, map_api_error, map_deserialization_error};
+use crate::options::RequestOptions;
+use crate::rate_limit::{RateLimitInfo, RateLimiter};
+use crate::response::Response;
+use crate::retry::{DefaultRetryPolicy, MaxAttempts, RetryPolicy};
+use crate::transport::{HttpResponse, ReqwestTransport, ServerSentEventStream, Transport};
+use crate::types::{
+    CompleteRequest, CompleteResponse, CompleteResponseStream, CountTokensRequest, CountTokensResponse,
+    CreateMessageRequest, CreateMessageResponse, CreateMessageResponseStream, StreamEvent,
+};
 use crate::{
-    API_VERSION, API_VERSION_HEADER_KEY, AUTHORIZATION_HEADER_KEY, CLIENT_ID, CLIENT_ID_HEADER_KEY, DEFAULT_API_BASE,
-    DEFAULT_MODEL,
+    API_VERSION, API_VERSION_HEADER_KEY, AUTHORIZATION_HEADER_KEY, BETA_HEADER_KEY, CLIENT_ID, CLIENT_ID_HEADER_KEY,
+    DEFAULT_API_BASE, DEFAULT_MODEL, IDEMPOTENCY_KEY_HEADER_KEY,
 };
 
 /// The client to interact with the API.
-#[derive(Builder, Debug)]
+/// Clones share the same HTTP connection pool, rate limiter and rate limits.
+#[derive(Builder, Clone, Debug)]
 pub struct Client {
     /// The API key.
     pub api_key: String,
     /// The API base url.
     #[builder(default = "DEFAULT_API_BASE.to_string()")]
     pub api_base: String,
-    /// The model to use.
+    /// The model of the message, token counting and batch requests without one.
     #[builder(default = "DEFAULT_MODEL.to_string()")]
     pub default_model: String,
-    /// The HTTP client.
-    /// Don't allow the user to set this through the builder.
-    #[builder(setter(skip))]
+    /// The HTTP client, defaulted to `reqwest::Client::new()`. Set a custom one for proxies, root
+    /// certificates, connection pool sizing or timeouts. Unused when a `transport` is set.
+    #[builder(default)]
     pub http_client: reqwest::Client,
+    /// The transport sending the requests, defaulted to a [ReqwestTransport] over `http_client`.
+    /// Set a custom one to test without a server, replay recorded responses or use another HTTP
+    /// stack.
+    #[builder(default, setter(strip_option))]
+    pub transport: Option<Arc<dyn Transport>>,
     /// The exponential backoff strategy, defaulted to `Default::default()`.
     #[builder(default = "Default::default()")]
     pub backoff: backoff::ExponentialBackoff,
+    /// The policy deciding which failed requests are retried, defaulted to
+    /// [DefaultRetryPolicy].
+    #[builder(default = "Arc::new(DefaultRetryPolicy::default())")]
+    pub retry_policy: Arc<dyn RetryPolicy>,
+    /// An optional client-side limiter throttling the requests before they hit the API rate
+    /// limits, defaulted to `None`.
+    #[builder(default, setter(into, strip_option))]
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+    /// The beta features enabled for all the requests, sent in the `anthropic-beta` header.
+    /// Enable them one by one with `beta`. The betas of a request are added to these ones.
+    #[builder(default, setter(each(name = "beta", into)))]
+    pub betas: Vec<Beta>,
+    /// The options applied to all the requests, set with [Client::with_options].
+    #[builder(setter(skip))]
+    options: RequestOptions,
+    /// The rate limits reported by the latest response.
+    #[builder(setter(skip))]
+    rate_limit_info: Arc<Mutex<Option<RateLimitInfo>>>,
 }
 
 impl Client {
-
+    /// Send a message request.
+    /// # Arguments
+    /// * `request` - The message request.
+    /// # Returns
+    /// The message response.
+    /// # Errors
+    /// * `AnthropicError` - If the request fails.
     pub async fn create_message(&self, request: CreateMessageRequest) -> Result<CreateMessageResponse, AnthropicError> {
+        Ok(self.create_message_with_response(request).await?.into_data())
+    }
+
+    /// Send a message request and keep the HTTP response metadata.
+    /// # Arguments
+    /// * `request` - The message request.
+    /// # Returns
+    /// The message response along with the status, headers, request id, rate limits and latency.
+    /// # Errors
+    /// * `AnthropicError` - If the request fails.
+    pub async fn create_message_with_response(
+        &self,
+        mut request: CreateMessageRequest,
+    ) -> Result<Response<CreateMessageResponse>, AnthropicError> {
         if request.stream {
-            return Err(AnthropicError::InvalidArgument("When stream is true, use create_message_stream() instead".into()));
+            return Err(AnthropicError::InvalidArgument(
+                "When stream is true, use create_message_stream() instead".into(),
+            ));
         }
-        self.post("/v1/complete", request).await
+        self.default_model(&mut request.model);
+        let headers = request_headers(&request.extra_headers, request.idempotency_key.as_deref())?;
+        self.with_headers(&request.betas, &headers).post("/v1/messages", request).await
     }
 
-    pub async fn create_message_stream(&self, request: CreateMessageRequest) -> Result<CreateMessageResponseStream, AnthropicError> {
-        if request.stream {
-            return Err(AnthropicError::InvalidArgument("When stream is true, use create_message_stream() instead".into()));
+    /// Send a message request and stream the response events.
+    /// # Arguments
+    /// * `request` - The message request.
+    /// # Returns
+    /// A stream of message events.
+    /// # Errors
+    /// * `AnthropicError` - If the request fails.
+    pub async fn create_message_stream(
+        &self,
+        mut request: CreateMessageRequest,
+    ) -> Result<CreateMessageResponseStream, AnthropicError> {
+        if !request.stream {
+            return Err(AnthropicError::InvalidArgument("When stream is false, use create_message() instead".into()));
         }
-        Ok(self.post_stream("/v1/complete", request).await)
+        self.default_model(&mut request.model);
+        let headers = request_headers(&request.extra_headers, request.idempotency_key.as_deref())?;
+        let client = self.with_headers(&request.betas, &headers);
+        Ok(client.post_stream("/v1/messages", request))
+    }
+
+    /// Count the input tokens of a message request, without creating the message.
+    /// # Arguments
+    /// * `request` - The request to count, or a [CreateMessageRequest] converted into it.
+    /// # Returns
+    /// The number of input tokens.
+    /// # Errors
+    /// * `AnthropicError` - If the request fails.
+    pub async fn count_tokens(
+        &self,
+        request: impl Into<CountTokensRequest>,
+    ) -> Result<CountTokensResponse, AnthropicError> {
+        Ok(self.count_tokens_with_response(request).await?.into_data())
+    }
+
+    /// Count the input tokens of a message request and keep the HTTP response metadata.
+    /// # Arguments
+    /// * `request` - The request to count, or a [CreateMessageRequest] converted into it.
+    /// # Returns
+    /// The number of input tokens along with the status, headers, request id, rate limits and
+    /// latency.
+    /// # Errors
+    /// * `AnthropicError` - If the request fails.
+    pub async fn count_tokens_with_response(
+        &self,
+        request: impl Into<CountTokensRequest>,
+    ) -> Result<Response<CountTokensResponse>, AnthropicError> {
+        let mut request = request.into();
+        self.default_model(&mut request.model);
+        self.with_headers(&request.betas, &request.extra_headers).post("/v1/messages/count_tokens", request).await
     }
 
     /// Send a completion request.
@@ -58,6 +162,20 @@ impl Client {
     /// # Errors
     /// * `AnthropicError` - If the request fails.
     pub async fn complete(&self, request: CompleteRequest) -> Result<CompleteResponse, AnthropicError> {
+        Ok(self.complete_with_response(request).await?.into_data())
+    }
+
+    /// Send a completion request and keep the HTTP response metadata.
+    /// # Arguments
+    /// * `request` - The completion request.
+    /// # Returns
+    /// The completion response along with the status, headers, request id, rate limits and latency.
+    /// # Errors
+    /// * `AnthropicError` - If the request fails.
+    pub async fn complete_with_response(
+        &self,
+        request: CompleteRequest,
+    ) -> Result<Response<CompleteResponse>, AnthropicError> {
         if request.stream {
             return Err(AnthropicError::InvalidArgument("When stream is true, use complete_stream() instead".into()));
         }
@@ -68,7 +186,7 @@ impl Client {
         if !request.stream {
             return Err(AnthropicError::InvalidArgument("When stream is false, use complete() instead".into()));
         }
-        Ok(self.post_stream("/v1/complete", request).await)
+        Ok(self.post_stream("/v1/complete", request))
     }
 
     /// Get the API key.
@@ -81,7 +199,8 @@ impl Client {
         self.api_base.as_str()
     }
 
-    /// Generate the headers for the request.
+    /// Generate the headers for the request. Betas which are not valid header values are left
+    /// out, and fail the requests instead.
     pub fn headers(&self) -> HeaderMap {
         let mut headers = HeaderMap::new();
         headers.insert(AUTHORIZATION_HEADER_KEY, self.api_key().parse().unwrap());
@@ -89,199 +208,537 @@ impl Client {
         headers.insert(CONTENT_TYPE, "application/json".parse().unwrap());
         headers.insert(ACCEPT, "application/json".parse().unwrap());
         headers.insert(API_VERSION_HEADER_KEY, API_VERSION.parse().unwrap());
+        if let Ok(Some(betas)) = self.beta_header() {
+            headers.insert(BETA_HEADER_KEY, betas);
+        }
+        headers.extend(self.options.headers.clone());
         headers
     }
 
+    /// The `anthropic-beta` header enabling the betas, if any.
+    /// # Errors
+    /// * `AnthropicError::InvalidArgument` - If a beta is not a valid header value.
+    fn beta_header(&self) -> Result<Option<HeaderValue>, AnthropicError> {
+        if self.betas.is_empty() {
+            return Ok(None);
+        }
+        let betas: Vec<&str> = self.betas.iter().map(Beta::as_str).collect();
+        let betas = betas.join(",");
+        HeaderValue::from_str(&betas)
+            .map(Some)
+            .map_err(|_| AnthropicError::InvalidArgument(format!("invalid betas: {betas:?}")))
+    }
+
+    /// Use the default model of the client for a request without one.
+    pub(crate) fn default_model(&self, model: &mut String) {
+        if model.is_empty() {
+            model.clone_from(&self.default_model);
+        }
+    }
+
+    /// Get a client sending the requests with the `beta` feature enabled.
+    pub(crate) fn with_beta(&self, beta: Beta) -> Client {
+        self.with_headers(&[beta], &HeaderMap::new())
+    }
+
+    /// Get a client sending the requests with the `betas` enabled in addition to its own, and the
+    /// `extra_headers` replacing the headers of the same name. An `anthropic-beta` extra header
+    /// replaces all the betas.
+    pub(crate) fn with_headers(&self, betas: &[Beta], extra_headers: &HeaderMap) -> Client {
+        let mut client = self.clone();
+        for beta in betas {
+            if !client.betas.contains(beta) {
+                client.betas.push(beta.clone());
+            }
+        }
+        client.options.headers.extend(extra_headers.clone());
+        client
+    }
+
+    /// Get a client sending the requests with the `options` applied on top of the ones of this
+    /// client. Both clients share the same HTTP connection pool, rate limiter and rate limits.
+    /// # Arguments
+    /// * `options` - The options of the requests.
+    /// # Returns
+    /// The client applying the options.
+    pub fn with_options(&self, options: RequestOptions) -> Client {
+        let mut client = self.clone();
+        if let Some(max_retries) = options.max_retries {
+            let policy = client.retry_policy.clone();
+            client.retry_policy = Arc::new(MaxAttempts { policy, max_attempts: max_retries.saturating_add(1) });
+        }
+        client.options.merge(options);
+        client
+    }
+
+    /// Start building a request to {path} with the API base url and headers.
+    /// # Arguments
+    /// * `method` - The HTTP method.
+    /// * `path` - The path to send the request to.
+    /// # Returns
+    /// The request builder.
+    /// # Errors
+    /// * `AnthropicError::InvalidArgument` - If a beta is not a valid header value.
+    pub(crate) fn request(&self, method: Method, path: &str) -> Result<reqwest::RequestBuilder, AnthropicError> {
+        // Fail instead of sending the request without its invalid betas.
+        self.beta_header()?;
+        let mut request = self
+            .http_client
+            .request(method, format!("{}{path}", self.api_base()))
+            .bearer_auth(self.api_key())
+            .headers(self.headers());
+        if let Some(timeout) = self.options.timeout {
+            request = request.timeout(timeout);
+        }
+        if !self.options.query.is_empty() {
+            request = request.query(&self.options.query);
+        }
+        Ok(request)
+    }
+
     /// Make a POST request to {path} and deserialize the response body.
     /// # Arguments
     /// * `path` - The path to POST to.
     /// * `request` - The request body.
     /// # Returns
-    /// The response body.
+    /// The response body along with the HTTP response metadata.
     /// # Errors
     /// * `AnthropicError` - If the request fails.
-    pub(crate) async fn post<I, O>(&self, path: &str, request: I) -> Result<O, AnthropicError>
+    pub(crate) async fn post<I, O>(&self, path: &str, request: I) -> Result<Response<O>, AnthropicError>
     where
         I: Serialize,
         O: DeserializeOwned,
     {
-        let request = self
-            .http_client
-            .post(format!("{}{path}", self.api_base()))
-            .bearer_auth(self.api_key())
-            .headers(self.headers())
-            .json(&request)
-            .build()?;
+        let request = self.request(Method::POST, path)?.json(&request).build()?;
+
+        self.execute(request).await
+    }
+
+    /// Make a GET request to {path} with the `query` parameters and deserialize the response body.
+    /// # Arguments
+    /// * `path` - The path to GET.
+    /// * `query` - The query parameters.
+    /// # Returns
+    /// The response body along with the HTTP response metadata.
+    /// # Errors
+    /// * `AnthropicError` - If the request fails.
+    pub(crate) async fn get<Q, O>(&self, path: &str, query: &Q) -> Result<Response<O>, AnthropicError>
+    where
+        Q: Serialize + ?Sized,
+        O: DeserializeOwned,
+    {
+        let request = self.request(Method::GET, path)?.query(query).build()?;
+
+        self.execute(request).await
+    }
+
+    /// Make a DELETE request to {path} and deserialize the response body.
+    /// # Arguments
+    /// * `path` - The path to DELETE.
+    /// # Returns
+    /// The response body along with the HTTP response metadata.
+    /// # Errors
+    /// * `AnthropicError` - If the request fails.
+    pub(crate) async fn delete<O>(&self, path: &str) -> Result<Response<O>, AnthropicError>
+    where
+        O: DeserializeOwned,
+    {
+        let request = self.request(Method::DELETE, path)?.build()?;
 
         self.execute(request).await
     }
 
     /// Make a streaming POST request to {path} and create a Stream of the retuned Server-Sent
-    /// Events # Arguments
+    /// Events. Failures allowed by the retry policy are retried until the first event is received.
+    /// # Arguments
     /// * `path` - The path to POST to.
     /// * `request` - The request body.
     /// # Returns
     /// A Stream of Server-Sent Events
     /// # Errors
     /// * `AnthropicError` - If the request fails.
-    pub(crate) async fn post_stream<I, O>(
+    pub(crate) fn post_stream<I, O>(
         &self,
         path: &str,
         request: I,
     ) -> Pin<Box<dyn Stream<Item = Result<O, AnthropicError>> + Send>>
     where
         I: Serialize,
-        O: DeserializeOwned + Send + 'static,
+        O: StreamItem,
     {
-        let event_source = self
-            .http_client
-            .post(format!("{}{path}", self.api_base()))
-            .bearer_auth(self.api_key())
-            .headers(self.headers())
-            .json(&request)
-            .eventsource()
-            .unwrap();
+        let request = match self.request(Method::POST, path).and_then(|builder| Ok(builder.json(&request).build()?)) {
+            Ok(request) => request,
+            Err(e) => return Box::pin(tokio_stream::once(Err(e))),
+        };
+
+        stream(
+            self.transport(),
+            request,
+            self.backoff.clone(),
+            self.retry_policy.clone(),
+            self.rate_limiter.clone(),
+            self.rate_limit_info.clone(),
+        )
+    }
 
-        stream(event_source).await
+    /// The rate limits reported by the latest response received by this client, if any.
+    pub fn rate_limit_info(&self) -> Option<RateLimitInfo> {
+        self.rate_limit_info.lock().unwrap().clone()
     }
 
-    /// Deserialize response body from either error object or actual response object.
+    /// The transport sending the requests.
+    fn transport(&self) -> Arc<dyn Transport> {
+        match &self.transport {
+            Some(transport) => transport.clone(),
+            None => Arc::new(ReqwestTransport::new(self.http_client.clone())),
+        }
+    }
+
+    /// Wait for the client-side rate limiter, if any, to allow sending a request.
+    async fn acquire_rate_limit(&self) {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire().await;
+        }
+    }
+
+    /// Execute any HTTP requests and deserialize the response body.
     /// # Arguments
-    /// * `response` - The response to process.
+    /// * `request` - The request to execute.
     /// # Returns
-    /// The response body.
+    /// The response body along with the HTTP response metadata.
     /// # Errors
     /// * `AnthropicError` - If the request fails.
-    async fn process_response<O>(&self, response: reqwest::Response) -> Result<O, AnthropicError>
+    pub(crate) async fn execute<O>(&self, request: reqwest::Request) -> Result<Response<O>, AnthropicError>
     where
         O: DeserializeOwned,
     {
-        let status = response.status();
+        let (response, started) = self.send(request).await?;
+        let status = response.status;
+        let headers = response.headers.clone();
         let bytes = response.bytes().await?;
 
-        if !status.is_success() {
-            let wrapped_error: WrappedError =
-                serde_json::from_slice(bytes.as_ref()).map_err(|e| map_deserialization_error(e, bytes.as_ref()))?;
-
-            return Err(AnthropicError::ApiError(wrapped_error.error));
-        }
-
-        let response: O =
+        let data: O =
             serde_json::from_slice(bytes.as_ref()).map_err(|e| map_deserialization_error(e, bytes.as_ref()))?;
-        Ok(response)
+        Ok(Response::new(data, status, headers, started.elapsed()))
     }
 
-    /// Execute any HTTP requests and retry on rate limit, except streaming ones as they cannot be
-    /// cloned for retrying.
+    /// Send any HTTP requests and retry the failures allowed by the retry policy. Requests with a
+    /// streaming body cannot be cloned and are sent only once.
     /// # Arguments
-    /// * `request` - The request to execute.
+    /// * `request` - The request to send.
     /// # Returns
-    /// The response body.
+    /// The successful response with its body left unread, and when its attempt was sent.
     /// # Errors
-    /// * `AnthropicError` - If the request fails.
-    async fn execute<O>(&self, request: reqwest::Request) -> Result<O, AnthropicError>
-    where
-        O: DeserializeOwned,
-    {
-        let client = self.http_client.clone();
+    /// * `AnthropicError` - If the request fails or the response is not successful.
+    pub(crate) async fn send(&self, request: reqwest::Request) -> Result<(HttpResponse, Instant), AnthropicError> {
+        let transport = self.transport();
 
         match request.try_clone() {
             // Only clone-able requests can be retried
             Some(request) => {
-                backoff::future::retry(self.backoff.clone(), || async {
-                    let response = client
-                        .execute(request.try_clone().unwrap())
-                        .await
-                        .map_err(AnthropicError::Reqwest)
-                        .map_err(backoff::Error::Permanent)?;
-
-                    let status = response.status();
-                    let bytes =
-                        response.bytes().await.map_err(AnthropicError::Reqwest).map_err(backoff::Error::Permanent)?;
-
-                    // Deserialize response body from either error object or actual response object
-                    if !status.is_success() {
-                        let wrapped_error: WrappedError = serde_json::from_slice(bytes.as_ref())
-                            .map_err(|e| map_deserialization_error(e, bytes.as_ref()))
-                            .map_err(backoff::Error::Permanent)?;
-
-                        if status.as_u16() == 429
-                            // API returns 429 also when:
-                            // "You exceeded your current quota, please check your plan and billing details."
-                            && wrapped_error.error.r#type != "insufficient_quota"
-                        {
-                            // Rate limited retry...
-                            return Err(backoff::Error::Transient {
-                                err: AnthropicError::ApiError(wrapped_error.error),
-                                retry_after: None,
-                            });
-                        } else {
-                            return Err(backoff::Error::Permanent(AnthropicError::ApiError(wrapped_error.error)));
+                let mut attempt = 0;
+                backoff::future::retry(self.backoff.clone(), || {
+                    attempt += 1;
+                    let attempt = attempt;
+                    let transport = transport.clone();
+                    let request = request.try_clone().unwrap();
+                    let policy = self.retry_policy.clone();
+
+                    async move {
+                        let can_retry = attempt < policy.max_attempts();
+                        self.acquire_rate_limit().await;
+                        let started = Instant::now();
+                        let response = match transport.send(request).await {
+                            Ok(response) => response,
+                            Err(e) if can_retry && policy.should_retry_error(&e) => {
+                                return Err(backoff::Error::transient(e));
+                            }
+                            Err(e) => return Err(backoff::Error::Permanent(e)),
+                        };
+
+                        let status = response.status;
+                        observe_rate_limits(&response.headers, self.rate_limiter.as_deref(), &self.rate_limit_info);
+                        if status.is_success() {
+                            return Ok((response, started));
                         }
-                    }
 
-                    let response: O = serde_json::from_slice(bytes.as_ref())
-                        .map_err(|e| map_deserialization_error(e, bytes.as_ref()))
-                        .map_err(backoff::Error::Permanent)?;
-                    Ok(response)
+                        // Deserialize the error object of the response body
+                        let headers = response.headers.clone();
+                        let bytes = response.bytes().await.map_err(backoff::Error::Permanent)?;
+                        let retry = can_retry && policy.should_retry_status(status, &headers);
+                        let retry_after = policy.retry_after(&headers);
+                        let err = map_api_error(status, headers, bytes.as_ref());
+
+                        if retry {
+                            return Err(backoff::Error::Transient { err, retry_after });
+                        }
+                        Err(backoff::Error::Permanent(err))
+                    }
                 })
                 .await
             }
             None => {
-                let response = client.execute(request).await?;
-                self.process_response(response).await
+                self.acquire_rate_limit().await;
+                let started = Instant::now();
+                let response = transport.send(request).await?;
+
+                let status = response.status;
+                observe_rate_limits(&response.headers, self.rate_limiter.as_deref(), &self.rate_limit_info);
+                if !status.is_success() {
+                    let headers = response.headers.clone();
+                    let bytes = response.bytes().await?;
+                    return Err(map_api_error(status, headers, bytes.as_ref()));
+                }
+                Ok((response, started))
             }
         }
     }
 }
 
-async fn stream<O>(mut event_source: EventSource) -> Pin<Box<dyn Stream<Item = Result<O, AnthropicError>> + Send>>
+/// Maps the Server-Sent Events of a streaming endpoint onto the items of the returned stream.
+pub(crate) trait StreamItem: Sized + Send + 'static {
+    /// Parse the `data` of an event named `event`.
+    /// Returns `None` for events that should not be forwarded to the caller.
+    fn from_event(event: &str, data: &str) -> Option<Result<Self, AnthropicError>>;
+
+    /// Whether the server closes the stream after this item.
+    fn is_terminal(&self) -> bool {
+        false
+    }
+}
+
+impl StreamItem for CompleteResponse {
+    fn from_event(event: &str, data: &str) -> Option<Result<Self, AnthropicError>> {
+        match event {
+            "completion" => Some(parse_event_data(data)),
+            "error" => Some(Err(parse_error_event(data))),
+            _ => None,
+        }
+    }
+}
+
+impl StreamItem for StreamEvent {
+    fn from_event(event: &str, data: &str) -> Option<Result<Self, AnthropicError>> {
+        match event {
+            "ping" => None,
+            "error" => Some(Err(parse_error_event(data))),
+            // Unknown event names still deserialize into `StreamEvent::Unknown`.
+            _ => Some(parse_event_data(data)),
+        }
+    }
+
+    fn is_terminal(&self) -> bool {
+        matches!(self, StreamEvent::MessageStop)
+    }
+}
+
+/// The headers of a request: its extra headers and its idempotency key.
+/// # Errors
+/// * `AnthropicError::InvalidArgument` - If the idempotency key is not a valid header value.
+pub(crate) fn request_headers(
+    extra_headers: &HeaderMap,
+    idempotency_key: Option<&str>,
+) -> Result<HeaderMap, AnthropicError> {
+    let mut headers = extra_headers.clone();
+    if let Some(idempotency_key) = idempotency_key {
+        let value = HeaderValue::from_str(idempotency_key)
+            .map_err(|_| AnthropicError::InvalidArgument(format!("invalid idempotency key: {idempotency_key:?}")))?;
+        headers.insert(IDEMPOTENCY_KEY_HEADER_KEY, value);
+    }
+    Ok(headers)
+}
+
+fn parse_event_data<O: DeserializeOwned>(data: &str) -> Result<O, AnthropicError> {
+    serde_json::from_str(data).map_err(|e| map_deserialization_error(e, data.as_bytes()))
+}
+
+fn parse_error_event(data: &str) -> AnthropicError {
+    match serde_json::from_str::<WrappedError>(data) {
+        Ok(wrapped_error) => AnthropicError::ApiError(Box::new(wrapped_error.error)),
+        Err(e) => map_deserialization_error(e, data.as_bytes()),
+    }
+}
+
+/// Record the rate limits reported in the headers of a response, and sync the rate limiter with
+/// them.
+fn observe_rate_limits(
+    headers: &HeaderMap,
+    rate_limiter: Option<&RateLimiter>,
+    rate_limit_info: &Mutex<Option<RateLimitInfo>>,
+) {
+    let info = RateLimitInfo::from_headers(headers);
+    if info == RateLimitInfo::default() {
+        return;
+    }
+    if let Some(rate_limiter) = rate_limiter {
+        rate_limiter.update(&info);
+    }
+    *rate_limit_info.lock().unwrap() = Some(info);
+}
+
+/// The state of a streaming request between two items.
+struct EventStream {
+    transport: Arc<dyn Transport>,
+    request: reqwest::Request,
+    backoff: backoff::ExponentialBackoff,
+    policy: Arc<dyn RetryPolicy>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    rate_limit_info: Arc<Mutex<Option<RateLimitInfo>>>,
+    attempt: u32,
+    /// The events of the current attempt, `None` until it is sent.
+    events: Option<ServerSentEventStream>,
+    /// Whether an event of the current attempt was received. Once it is, the request cannot be
+    /// sent again without duplicating it.
+    received: bool,
+    /// Whether the stream ended, after its last item or an error.
+    done: bool,
+}
+
+/// Send a streaming request, retrying it until its first event is received.
+/// # Returns
+/// A lazy stream of the items of the response, which sends no request until it is polled. It ends
+/// after the terminal item or the first error.
+fn stream<O>(
+    transport: Arc<dyn Transport>,
+    request: reqwest::Request,
+    mut backoff: backoff::ExponentialBackoff,
+    policy: Arc<dyn RetryPolicy>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    rate_limit_info: Arc<Mutex<Option<RateLimitInfo>>>,
+) -> Pin<Box<dyn Stream<Item = Result<O, AnthropicError>> + Send>>
 where
-    O: DeserializeOwned + Send + 'static,
+    O: StreamItem,
 {
-    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
-
-    // TODO: Uncomment
-    #[cfg(not(target_arch = "wasm32"))]
-    {
-        tokio::spawn(async move {
-            while let Some(ev) = event_source.next().await {
-                match ev {
-                    Ok(event) => match event {
-                        Event::Open => continue,
-                        Event::Message(message) => {
-                            match message.event.as_ref() {
-                                "ping" => continue,
-                                "completion" => {
-                                    let response = match serde_json::from_str::<O>(&message.data) {
-                                        Ok(output) => Ok(output),
-                                        Err(e) => Err(map_deserialization_error(e, message.data.as_bytes())),
-                                    };
-
-                                    if let Err(_e) = tx.send(response) {
-                                        // rx dropped
-                                        break;
-                                    }
-                                }
-                                _ => continue,
-                            }
-                        }
-                    },
-                    Err(e) => {
-                        if let Err(_e) = tx.send(Err(AnthropicError::StreamError(e.to_string()))) {
-                            // rx dropped
-                            break;
-                        }
+    backoff.reset();
+    let state = EventStream {
+        transport,
+        request,
+        backoff,
+        policy,
+        rate_limiter,
+        rate_limit_info,
+        attempt: 1,
+        events: None,
+        received: false,
+        done: false,
+    };
+
+    Box::pin(futures_util::stream::unfold(state, |mut state| async move {
+        loop {
+            if state.done {
+                return None;
+            }
+            let failure = match &mut state.events {
+                None => match stream_attempt(
+                    state.transport.as_ref(),
+                    &state.request,
+                    state.policy.as_ref(),
+                    state.rate_limiter.as_deref(),
+                    &state.rate_limit_info,
+                )
+                .await
+                {
+                    Ok(events) => {
+                        state.events = Some(events);
+                        state.received = false;
+                        continue;
+                    }
+                    Err(failure) => failure,
+                },
+                Some(events) => match events.next().await {
+                    None => return None,
+                    Some(Ok(event)) => {
+                        state.received = true;
+                        let Some(item) = O::from_event(&event.event, &event.data) else {
+                            continue;
+                        };
+                        state.done = match &item {
+                            Ok(item) => item.is_terminal(),
+                            // The API does not send anything meaningful after an error event.
+                            Err(_) => true,
+                        };
+                        return Some((item, state));
                     }
+                    Some(Err(e)) if !state.received => {
+                        let retryable = state.policy.should_retry_error(&e);
+                        StreamFailure { error: e, retryable, retry_after: None }
+                    }
+                    Some(Err(e)) => StreamFailure::permanent(e),
+                },
+            };
+
+            let StreamFailure { error, retryable, retry_after } = failure;
+            if retryable && state.attempt < state.policy.max_attempts() {
+                if let Some(delay) = state.backoff.next_backoff() {
+                    tokio::time::sleep(retry_after.unwrap_or(delay)).await;
+                    state.attempt += 1;
+                    state.events = None;
+                    continue;
                 }
             }
 
-            event_source.close();
-        });
+            state.done = true;
+            let error = match retryable {
+                true => AnthropicError::StreamRetriesExhausted { attempt: state.attempt, source: Box::new(error) },
+                false => error,
+            };
+            return Some((Err(error), state));
+        }
+    }))
+}
+
+/// A failed attempt of a streaming request.
+struct StreamFailure {
+    error: AnthropicError,
+    /// Whether the retry policy allows retrying the request.
+    retryable: bool,
+    /// The delay requested by the server before retrying.
+    retry_after: Option<Duration>,
+}
+
+impl StreamFailure {
+    fn permanent(error: AnthropicError) -> Self {
+        Self { error, retryable: false, retry_after: None }
     }
-    
+}
 
-    Box::pin(tokio_stream::wrappers::UnboundedReceiverStream::new(rx))
+/// Send an attempt of a streaming request.
+/// # Returns
+/// The events of the response, the failure of the attempt otherwise.
+async fn stream_attempt(
+    transport: &dyn Transport,
+    request: &reqwest::Request,
+    policy: &dyn RetryPolicy,
+    rate_limiter: Option<&RateLimiter>,
+    rate_limit_info: &Mutex<Option<RateLimitInfo>>,
+) -> Result<ServerSentEventStream, StreamFailure> {
+    if let Some(rate_limiter) = rate_limiter {
+        rate_limiter.acquire().await;
+    }
+    let Some(request) = request.try_clone() else {
+        return Err(StreamFailure::permanent(AnthropicError::StreamError("request cannot be cloned".to_string())));
+    };
+    let response = match transport.open_event_stream(request).await {
+        Ok(response) => response,
+        Err(e) => {
+            let retryable = policy.should_retry_error(&e);
+            return Err(StreamFailure { error: e, retryable, retry_after: None });
+        }
+    };
+
+    observe_rate_limits(&response.headers, rate_limiter, rate_limit_info);
+    if !response.status.is_success() {
+        let status = response.status;
+        let headers = response.headers.clone();
+        let retryable = policy.should_retry_status(status, &headers);
+        let retry_after = policy.retry_after(&headers);
+        let error = match response.bytes().await {
+            Ok(bytes) => map_api_error(status, headers, bytes.as_ref()),
+            Err(e) => e,
+        };
+        return Err(StreamFailure { error, retryable, retry_after });
+    }
+    Ok(response.events())
 }
 
 impl TryFrom<AnthropicConfig> for Client {
@@ -291,12 +748,19 @@ impl TryFrom<AnthropicConfig> for Client {
     /// # Arguments
     /// * `value` - The configuration.
     fn try_from(value: AnthropicConfig) -> Result<Self, Self::Error> {
+        let http_client = value.http_client()?;
         Ok(Self {
             api_key: value.api_key,
             api_base: value.api_base.unwrap_or_else(|| DEFAULT_API_BASE.to_string()),
             default_model: value.default_model.unwrap_or_else(|| DEFAULT_MODEL.to_string()),
-            http_client: reqwest::Client::new(),
+            http_client,
+            transport: None,
             backoff: Default::default(),
+            retry_policy: Arc::new(DefaultRetryPolicy::default()),
+            rate_limiter: None,
+            betas: Vec::new(),
+            options: RequestOptions::default(),
+            rate_limit_info: Default::default(),
         })
     }
 }
@@ -1,4 +1,6 @@
 //! General configuration
+use std::time::Duration;
+
 use config::Config;
 use serde_derive::Deserialize;
 
@@ -10,6 +12,12 @@ pub struct AnthropicConfig {
     pub api_key: String,
     pub api_base: Option<String>,
     pub default_model: Option<String>,
+    /// The timeout of the requests, from connecting to receiving the end of the response body.
+    pub timeout_secs: Option<u64>,
+    /// The timeout of the connection to the API.
+    pub connect_timeout_secs: Option<u64>,
+    /// The url of the proxy to send all the requests through, e.g. `http://proxy:8080`.
+    pub proxy: Option<String>,
 }
 
 impl AnthropicConfig {
@@ -17,6 +25,23 @@ impl AnthropicConfig {
     pub fn new() -> Result<Self, AnthropicError> {
         CONFIG.clone().try_deserialize().map_err(|e| e.into())
     }
+
+    /// Build the HTTP client with the timeouts and proxy of the configuration.
+    /// # Errors
+    /// * `AnthropicError::Reqwest` - If the proxy url is invalid or the client cannot be built.
+    pub fn http_client(&self) -> Result<reqwest::Client, AnthropicError> {
+        let mut builder = reqwest::Client::builder();
+        if let Some(timeout_secs) = self.timeout_secs {
+            builder = builder.timeout(Duration::from_secs(timeout_secs));
+        }
+        if let Some(connect_timeout_secs) = self.connect_timeout_secs {
+            builder = builder.connect_timeout(Duration::from_secs(connect_timeout_secs));
+        }
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+        }
+        Ok(builder.build()?)
+    }
 }
 
 impl Default for AnthropicConfig {
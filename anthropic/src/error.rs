@@ -1,18 +1,45 @@
 //! Definition of errors used in the library.
+use std::fmt;
+
 use config::ConfigError;
+use reqwest::StatusCode;
+use reqwest::header::HeaderMap;
 use serde::Deserialize;
 
+use crate::response::request_id;
+use crate::retry::{DefaultRetryPolicy, RetryPolicy};
+use crate::types::InputMessage;
+
 #[derive(Debug, thiserror::Error)]
 pub enum AnthropicError {
     /// Underlying error from reqwest library after an API call was made
     #[error("http error: {0}")]
     Reqwest(#[from] reqwest::Error),
-    /// OpenAI returns error object with details of API call failure
-    #[error("{}: {}", .0.r#type, .0.message)]
-    ApiError(ApiError),
+    /// A custom [Transport](crate::transport::Transport) received no response
+    #[error("transport error: {source}")]
+    Transport {
+        /// The error of the transport.
+        source: Box<dyn std::error::Error + Send + Sync>,
+        /// Whether sending the request again may succeed, e.g. after a connection failure.
+        retryable: bool,
+    },
+    /// Anthropic returns error object with details of API call failure
+    #[error("{0}")]
+    ApiError(Box<ApiError>),
+    /// A non-success response without an API error object, e.g. an HTML page from a proxy
+    #[error("unexpected response with status {status}: {body}")]
+    UnexpectedResponse {
+        /// The HTTP status of the response.
+        status: StatusCode,
+        /// The start of the raw response body, truncated to [MAX_BODY_SNIPPET_CHARS] characters.
+        body: String,
+    },
     /// Error when a response cannot be deserialized into a Rust type
     #[error("failed to deserialize api response: {0}")]
     JSONDeserialize(serde_json::Error),
+    /// Error when reading local files
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
     /// Error on SSE streaming
     #[error("stream failed: {0}")]
     StreamError(String),
@@ -20,15 +47,116 @@ pub enum AnthropicError {
     /// or when builder fails to build request before making API call
     #[error("invalid args: {0}")]
     InvalidArgument(String),
+    /// The input of a `tool_use` block does not match the input type of the tool
+    #[error("invalid input for tool {tool}: {source}")]
+    InvalidToolInput { tool: String, source: serde_json::Error },
+    /// The tool use loop did not end within the allowed number of iterations
+    #[error("tool use did not end after {iterations} iterations")]
+    MaxIterations {
+        /// The number of messages sent.
+        iterations: usize,
+        /// The conversation so far, ending with the results of the last requested tools.
+        messages: Vec<InputMessage>,
+    },
+    /// A streaming request failed before any event was received and could not be retried anymore
+    #[error("stream attempt {attempt} failed: {source}")]
+    StreamRetriesExhausted { attempt: u32, source: Box<AnthropicError> },
+    /// The processing of a message batch did not end within the allowed time
+    #[error("batch {batch_id} did not end within {timeout:?}")]
+    BatchTimeout { batch_id: String, timeout: std::time::Duration },
 }
 
+/// Maximum number of characters of a raw response body kept in errors and logs.
+pub const MAX_BODY_SNIPPET_CHARS: usize = 512;
+
 /// Anthropic API returns error object on failure
 #[derive(Debug, Deserialize)]
 pub struct ApiError {
+    /// The kind of error.
+    #[serde(rename = "type")]
+    pub kind: ApiErrorKind,
+    /// A human readable description of the error.
     pub message: String,
-    pub r#type: String,
-    pub param: Option<serde_json::Value>,
-    pub code: Option<serde_json::Value>,
+    /// The HTTP status of the response, `None` for errors sent as stream events.
+    #[serde(skip)]
+    pub status: Option<StatusCode>,
+    /// The `request-id` header of the response, to give when contacting support.
+    #[serde(skip)]
+    pub request_id: Option<String>,
+    /// The headers of the response, empty for errors sent as stream events.
+    #[serde(skip)]
+    pub headers: HeaderMap,
+}
+
+impl ApiError {
+    /// Whether sending the request again may succeed, as decided by [DefaultRetryPolicy].
+    pub fn is_retryable(&self) -> bool {
+        match self.status {
+            Some(status) => DefaultRetryPolicy::default().should_retry_status(status, &self.headers),
+            None => {
+                matches!(
+                    self.kind,
+                    ApiErrorKind::RateLimitError | ApiErrorKind::ApiError | ApiErrorKind::OverloadedError
+                )
+            }
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind, self.message)?;
+        if let Some(request_id) = &self.request_id {
+            write!(f, " (request id: {request_id})")?;
+        }
+        Ok(())
+    }
+}
+
+/// The kind of an [ApiError].
+/// Ref: https://docs.anthropic.com/en/api/errors
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiErrorKind {
+    /// There was an issue with the format or content of the request.
+    InvalidRequestError,
+    /// There is an issue with the API key.
+    AuthenticationError,
+    /// The API key does not have permission to use the specified resource.
+    PermissionError,
+    /// The requested resource was not found.
+    NotFoundError,
+    /// The account has hit a rate limit.
+    RateLimitError,
+    /// An unexpected error occurred internal to Anthropic's systems.
+    ApiError,
+    /// The API is temporarily overloaded.
+    OverloadedError,
+    /// An error kind unknown to this version of the SDK.
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+impl ApiErrorKind {
+    /// The `type` of the error as sent by the API.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::InvalidRequestError => "invalid_request_error",
+            Self::AuthenticationError => "authentication_error",
+            Self::PermissionError => "permission_error",
+            Self::NotFoundError => "not_found_error",
+            Self::RateLimitError => "rate_limit_error",
+            Self::ApiError => "api_error",
+            Self::OverloadedError => "overloaded_error",
+            Self::Unknown(kind) => kind,
+        }
+    }
+}
+
+impl fmt::Display for ApiErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 impl From<ConfigError> for AnthropicError {
@@ -43,6 +171,31 @@ pub(crate) struct WrappedError {
     pub(crate) error: ApiError,
 }
 
-pub(crate) fn map_deserialization_error(e: serde_json::Error, _bytes: &[u8]) -> AnthropicError {
+pub(crate) fn map_deserialization_error(e: serde_json::Error, bytes: &[u8]) -> AnthropicError {
+    log::error!("failed to deserialize api response: {e}, body: {}", body_snippet(bytes));
     AnthropicError::JSONDeserialize(e)
 }
+
+/// Deserialize the error object of a non-success response, along with its status and headers.
+/// Bodies without an error object become [AnthropicError::UnexpectedResponse].
+pub(crate) fn map_api_error(status: StatusCode, headers: HeaderMap, bytes: &[u8]) -> AnthropicError {
+    match serde_json::from_slice::<WrappedError>(bytes) {
+        Ok(WrappedError { mut error }) => {
+            error.status = Some(status);
+            error.request_id = request_id(&headers);
+            error.headers = headers;
+            AnthropicError::ApiError(Box::new(error))
+        }
+        Err(_) => AnthropicError::UnexpectedResponse { status, body: body_snippet(bytes) },
+    }
+}
+
+/// The start of a raw response body, lossily decoded and truncated to [MAX_BODY_SNIPPET_CHARS].
+fn body_snippet(bytes: &[u8]) -> String {
+    let body = String::from_utf8_lossy(bytes);
+    let body = body.trim();
+    match body.char_indices().nth(MAX_BODY_SNIPPET_CHARS) {
+        Some((end, _)) => format!("{}...", &body[..end]),
+        None => body.to_string(),
+    }
+}
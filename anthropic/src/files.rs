@@ -0,0 +1,238 @@
+//! Files API, uploading files once to reference them by id in messages.
+//! Ref: https://docs.anthropic.com/en/api/files-create
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use reqwest::Method;
+use reqwest::header::{CONTENT_TYPE, HeaderValue};
+use reqwest::multipart::{Form, Part};
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncRead;
+use tokio_util::io::ReaderStream;
+
+use crate::beta::Beta;
+use crate::client::Client;
+use crate::error::AnthropicError;
+use crate::pagination::{ListParams, Page, PageStream};
+use crate::response::Response;
+use crate::types::ImageMediaType;
+
+/// The path of the Files API.
+const FILES_PATH: &str = "/v1/files";
+/// The start of the boundaries of the multipart bodies built in memory.
+const BOUNDARY_PREFIX: &str = "anthropic-rs-boundary";
+
+/// A file uploaded with the Files API.
+#[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
+pub struct FileMetadata {
+    /// The id of the file, referenced by `file` content sources.
+    pub id: String,
+    /// The name of the uploaded file.
+    pub filename: String,
+    /// The MIME type of the file.
+    pub mime_type: String,
+    /// The size of the file, in bytes.
+    pub size_bytes: u64,
+    /// When the file was uploaded.
+    pub created_at: DateTime<Utc>,
+    /// Whether the file can be downloaded. Only files created by tools can be.
+    #[serde(default)]
+    pub downloadable: bool,
+}
+
+/// The confirmation of a file deletion.
+#[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
+pub struct DeletedFile {
+    /// The id of the deleted file.
+    pub id: String,
+}
+
+impl Client {
+    /// Upload a file, its MIME type being detected from its content or extension.
+    /// # Arguments
+    /// * `path` - The path of the file.
+    /// # Returns
+    /// The uploaded file.
+    /// # Errors
+    /// * `AnthropicError::Io` - If the file cannot be read.
+    /// * `AnthropicError` - If the request fails.
+    pub async fn upload_file(&self, path: impl AsRef<Path>) -> Result<FileMetadata, AnthropicError> {
+        let path = path.as_ref();
+        let bytes = tokio::fs::read(path).await?;
+        let filename = path.file_name().map_or_else(|| "file".to_string(), |name| name.to_string_lossy().into_owned());
+        let mime_type = guess_mime_type(path, &bytes);
+
+        self.upload_file_bytes(filename, mime_type, bytes).await
+    }
+
+    /// Upload a file from memory. The upload is retried like the other requests.
+    /// # Arguments
+    /// * `filename` - The name of the file.
+    /// * `mime_type` - The MIME type of the file, e.g. `application/pdf`.
+    /// * `bytes` - The content of the file.
+    /// # Returns
+    /// The uploaded file.
+    /// # Errors
+    /// * `AnthropicError::InvalidArgument` - If the MIME type is not a valid header value.
+    /// * `AnthropicError` - If the request fails.
+    pub async fn upload_file_bytes(
+        &self,
+        filename: impl Into<String>,
+        mime_type: &str,
+        bytes: impl Into<Vec<u8>>,
+    ) -> Result<FileMetadata, AnthropicError> {
+        if HeaderValue::from_str(mime_type).is_err() {
+            return Err(AnthropicError::InvalidArgument(format!("invalid MIME type: {mime_type:?}")));
+        }
+        let bytes = bytes.into();
+        // A body in memory, unlike the streamed body of a `Form`, lets the request be cloned to
+        // be retried.
+        let boundary = boundary(&bytes);
+        let body = multipart_body(&boundary, &filename.into(), mime_type, &bytes);
+        self.upload(&boundary, |request| request.body(body)).await
+    }
+
+    /// Upload a file read as it is sent, without loading it in memory.
+    /// # Arguments
+    /// * `filename` - The name of the file.
+    /// * `mime_type` - The MIME type of the file, e.g. `application/pdf`.
+    /// * `reader` - The content of the file.
+    /// # Returns
+    /// The uploaded file.
+    /// # Errors
+    /// * `AnthropicError::InvalidArgument` - If the client has a custom transport, which cannot
+    ///   send streamed bodies.
+    /// * `AnthropicError` - If the request fails. The upload cannot be retried.
+    pub async fn upload_file_reader<R>(
+        &self,
+        filename: impl Into<String>,
+        mime_type: &str,
+        reader: R,
+    ) -> Result<FileMetadata, AnthropicError>
+    where
+        R: AsyncRead + Send + Sync + 'static,
+    {
+        if self.transport.is_some() {
+            return Err(AnthropicError::InvalidArgument(
+                "Streamed uploads are not supported by custom transports, use upload_file_bytes() instead".into(),
+            ));
+        }
+        let body = reqwest::Body::wrap_stream(ReaderStream::new(reader));
+        let part = Part::stream(body).file_name(filename.into()).mime_str(mime_type)?;
+        let form = Form::new().part("file", part);
+        let boundary = form.boundary().to_string();
+        self.upload(&boundary, |request| request.multipart(form)).await
+    }
+
+    /// List a page of the uploaded files, most recent first.
+    /// # Arguments
+    /// * `params` - The pagination parameters.
+    /// # Returns
+    /// The page of files.
+    /// # Errors
+    /// * `AnthropicError` - If the request fails.
+    pub async fn list_files(&self, params: &ListParams) -> Result<Page<FileMetadata>, AnthropicError> {
+        self.with_beta(Beta::FilesApi).get(FILES_PATH, params).await.map(Response::into_data)
+    }
+
+    /// List all the uploaded files, most recent first, fetching the pages as the stream is
+    /// consumed.
+    /// # Arguments
+    /// * `params` - The parameters of the first page.
+    /// # Returns
+    /// A stream of the files.
+    pub fn list_all_files(&self, params: ListParams) -> PageStream<FileMetadata> {
+        self.with_beta(Beta::FilesApi).paginate(FILES_PATH, params)
+    }
+
+    /// Get the metadata of an uploaded file.
+    /// # Arguments
+    /// * `file_id` - The id of the file.
+    /// # Returns
+    /// The file.
+    /// # Errors
+    /// * `AnthropicError` - If the request fails.
+    pub async fn get_file(&self, file_id: &str) -> Result<FileMetadata, AnthropicError> {
+        self.with_beta(Beta::FilesApi).get(&format!("{FILES_PATH}/{file_id}"), &()).await.map(Response::into_data)
+    }
+
+    /// Download the content of a file. Only [downloadable](FileMetadata::downloadable) files can
+    /// be downloaded.
+    /// # Arguments
+    /// * `file_id` - The id of the file.
+    /// # Returns
+    /// The content of the file.
+    /// # Errors
+    /// * `AnthropicError` - If the request fails.
+    pub async fn download_file(&self, file_id: &str) -> Result<Vec<u8>, AnthropicError> {
+        let client = self.with_beta(Beta::FilesApi);
+        let request = client.request(Method::GET, &format!("{FILES_PATH}/{file_id}/content"))?.build()?;
+        let (response, _) = client.send(request).await?;
+
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    /// Delete an uploaded file.
+    /// # Arguments
+    /// * `file_id` - The id of the file.
+    /// # Returns
+    /// The confirmation of the deletion.
+    /// # Errors
+    /// * `AnthropicError` - If the request fails.
+    pub async fn delete_file(&self, file_id: &str) -> Result<DeletedFile, AnthropicError> {
+        self.with_beta(Beta::FilesApi).delete(&format!("{FILES_PATH}/{file_id}")).await.map(Response::into_data)
+    }
+
+    /// Send the upload request, whose multipart body with the `boundary` is set by `body`.
+    async fn upload(
+        &self,
+        boundary: &str,
+        body: impl FnOnce(reqwest::RequestBuilder) -> reqwest::RequestBuilder,
+    ) -> Result<FileMetadata, AnthropicError> {
+        let client = self.with_beta(Beta::FilesApi);
+        let content_type = format!("multipart/form-data; boundary={boundary}");
+        let mut request = body(client.request(Method::POST, FILES_PATH)?).build()?;
+        // Replace the JSON content type of the default headers.
+        request.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_str(&content_type).unwrap());
+
+        client.execute(request).await.map(Response::into_data)
+    }
+}
+
+/// A multipart boundary not found in the `content` of the file.
+fn boundary(content: &[u8]) -> String {
+    (0u64..)
+        .map(|n| format!("{BOUNDARY_PREFIX}-{n:016x}"))
+        .find(|boundary| !content.windows(boundary.len()).any(|window| window == boundary.as_bytes()))
+        .unwrap()
+}
+
+/// The `multipart/form-data` body of a form with the `content` of a file as `file` field.
+fn multipart_body(boundary: &str, filename: &str, mime_type: &str, content: &[u8]) -> Vec<u8> {
+    // Escape the file name as browsers and reqwest do.
+    let filename = filename.replace('"', "%22").replace('\r', "%0D").replace('\n', "%0A");
+    let mut body = format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{filename}\"\r\nContent-Type: \
+         {mime_type}\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend_from_slice(content);
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+    body
+}
+
+/// Detect PDFs and images from their content, and text files from their extension.
+fn guess_mime_type(path: &Path, bytes: &[u8]) -> &'static str {
+    if bytes.starts_with(b"%PDF-") {
+        return "application/pdf";
+    }
+    if let Some(media_type) = ImageMediaType::sniff(bytes) {
+        return media_type.as_str();
+    }
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("txt" | "md") => "text/plain",
+        Some("csv") => "text/csv",
+        Some("json") => "application/json",
+        _ => "application/octet-stream",
+    }
+}
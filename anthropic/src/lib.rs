@@ -6,7 +6,34 @@
 //! ```rust
 //! use std::error::Error;
 //! use anthropic::client::ClientBuilder;
-//! use anthropic::config::AnthropicConfig;
+//! use anthropic::types::{CreateMessageRequestBuilder, InputMessage};
+//! use dotenv::dotenv;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn Error>> {
+//! // Load the environment variables from the .env file.
+//! dotenv().ok();
+//!
+//! // Build with manual configuration.
+//! let client = ClientBuilder::default().api_key("my-api-key".to_string()).build()?;
+//!
+//! let message_request = CreateMessageRequestBuilder::default()
+//!     .messages(vec![InputMessage::user("How many toes do dogs have?")])
+//!     .max_tokens(256)
+//!     .build()?;
+//!
+//! // Send a message request, with the default model of the client.
+//! let _message_response_result = client.create_message(message_request).await;
+//! // Do something with the response.
+//!
+//! Ok(())
+//! }
+//! ```
+//!
+//! The legacy Text Completions API is available with `complete`:
+//! ```rust
+//! use std::error::Error;
+//! use anthropic::client::ClientBuilder;
 //! use anthropic::types::CompleteRequestBuilder;
 //! use anthropic::{AI_PROMPT, HUMAN_PROMPT};
 //! use dotenv::dotenv;
@@ -39,11 +66,29 @@ use rustc_version::version;
 #[macro_use]
 extern crate derive_builder;
 
+pub mod batches;
+pub mod beta;
 pub mod client;
 pub mod config;
 pub mod error;
+pub mod files;
+pub mod models;
+pub mod options;
+pub mod pagination;
+pub mod rate_limit;
+pub mod response;
+pub mod retry;
+pub mod stream;
+pub mod tools;
+pub mod transport;
 pub mod types;
 
+#[doc(hidden)]
+pub mod __private {
+    pub use serde;
+    pub use serde_json;
+}
+
 lazy_static! {
     /// A value to represent the client id of this SDK.
     pub static ref CLIENT_ID: String = client_id();
@@ -55,13 +100,21 @@ pub const HUMAN_PROMPT: &str = "\n\nHuman:";
 pub const AI_PROMPT: &str = "\n\nAssistant:";
 
 /// Default model to use.
-pub const DEFAULT_MODEL: &str = "claude-v1";
+pub const DEFAULT_MODEL: &str = "claude-sonnet-4-5-20250929";
+/// Default model of the legacy Text Completions API, which does not serve the current models.
+pub const DEFAULT_COMPLETION_MODEL: &str = "claude-2.1";
 /// Default v1 API base url.
 pub const DEFAULT_API_BASE: &str = "https://api.anthropic.com";
 /// Auth header key.
 const AUTHORIZATION_HEADER_KEY: &str = "x-api-key";
 /// Client id header key.
 const CLIENT_ID_HEADER_KEY: &str = "Client";
+/// Idempotency key header key, for the API to not process a retried request twice.
+const IDEMPOTENCY_KEY_HEADER_KEY: &str = "idempotency-key";
+/// Request id header key, identifying a request when contacting support.
+const REQUEST_ID_HEADER_KEY: &str = "request-id";
+/// Beta features header key.
+const BETA_HEADER_KEY: &str = "anthropic-beta";
 /// API version header key.
 /// Ref: https://docs.anthropic.com/claude/reference/versioning
 const API_VERSION_HEADER_KEY: &str = "anthropic-version";
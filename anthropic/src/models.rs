@@ -0,0 +1,150 @@
+//! Models API, and the ids of the current models.
+//! Ref: https://docs.anthropic.com/en/api/models-list
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::client::Client;
+use crate::error::AnthropicError;
+use crate::pagination::{ListParams, Page, PageStream};
+use crate::response::Response;
+
+/// The path of the Models API.
+const MODELS_PATH: &str = "/v1/models";
+
+/// The id of a model. Use [Model::Other] for models unknown to this version of the SDK, or
+/// aliases like `claude-sonnet-4-5`.
+///
+/// Converts into the `String` expected by the `model` of requests:
+/// `CreateMessageRequestBuilder::default().model(Model::ClaudeSonnet4_5)`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum Model {
+    /// `claude-opus-4-1-20250805`
+    ClaudeOpus4_1,
+    /// `claude-opus-4-20250514`
+    ClaudeOpus4,
+    /// `claude-sonnet-4-5-20250929`
+    ClaudeSonnet4_5,
+    /// `claude-sonnet-4-20250514`
+    ClaudeSonnet4,
+    /// `claude-haiku-4-5-20251001`
+    ClaudeHaiku4_5,
+    /// `claude-3-7-sonnet-20250219`
+    Claude3_7Sonnet,
+    /// `claude-3-5-haiku-20241022`
+    Claude3_5Haiku,
+    /// `claude-3-haiku-20240307`
+    Claude3Haiku,
+    /// Any other model id.
+    Other(String),
+}
+
+impl Model {
+    /// The known models, most capable first.
+    pub const KNOWN: [Model; 8] = [
+        Model::ClaudeOpus4_1,
+        Model::ClaudeOpus4,
+        Model::ClaudeSonnet4_5,
+        Model::ClaudeSonnet4,
+        Model::ClaudeHaiku4_5,
+        Model::Claude3_7Sonnet,
+        Model::Claude3_5Haiku,
+        Model::Claude3Haiku,
+    ];
+
+    /// The id of the model, as sent to the API.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Model::ClaudeOpus4_1 => "claude-opus-4-1-20250805",
+            Model::ClaudeOpus4 => "claude-opus-4-20250514",
+            Model::ClaudeSonnet4_5 => "claude-sonnet-4-5-20250929",
+            Model::ClaudeSonnet4 => "claude-sonnet-4-20250514",
+            Model::ClaudeHaiku4_5 => "claude-haiku-4-5-20251001",
+            Model::Claude3_7Sonnet => "claude-3-7-sonnet-20250219",
+            Model::Claude3_5Haiku => "claude-3-5-haiku-20241022",
+            Model::Claude3Haiku => "claude-3-haiku-20240307",
+            Model::Other(id) => id,
+        }
+    }
+}
+
+impl fmt::Display for Model {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<String> for Model {
+    fn from(id: String) -> Self {
+        Model::KNOWN.into_iter().find(|model| model.as_str() == id).unwrap_or(Model::Other(id))
+    }
+}
+
+impl From<&str> for Model {
+    fn from(id: &str) -> Self {
+        Model::from(id.to_string())
+    }
+}
+
+impl From<Model> for String {
+    fn from(model: Model) -> Self {
+        match model {
+            Model::Other(id) => id,
+            model => model.as_str().to_string(),
+        }
+    }
+}
+
+/// A model available through the API.
+#[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
+pub struct ModelInfo {
+    /// The id of the model.
+    pub id: String,
+    /// The human readable name of the model.
+    pub display_name: String,
+    /// When the model was released, or a date close to it.
+    pub created_at: DateTime<Utc>,
+}
+
+impl ModelInfo {
+    /// The model of this id.
+    pub fn model(&self) -> Model {
+        Model::from(self.id.as_str())
+    }
+}
+
+impl Client {
+    /// List a page of the available models, most recently released first.
+    /// # Arguments
+    /// * `params` - The pagination parameters.
+    /// # Returns
+    /// The page of models.
+    /// # Errors
+    /// * `AnthropicError` - If the request fails.
+    pub async fn list_models(&self, params: &ListParams) -> Result<Page<ModelInfo>, AnthropicError> {
+        self.get(MODELS_PATH, params).await.map(Response::into_data)
+    }
+
+    /// List all the available models, most recently released first, fetching the pages as the
+    /// stream is consumed.
+    /// # Arguments
+    /// * `params` - The parameters of the first page.
+    /// # Returns
+    /// A stream of the models.
+    pub fn list_all_models(&self, params: ListParams) -> PageStream<ModelInfo> {
+        self.paginate(MODELS_PATH, params)
+    }
+
+    /// Get a model.
+    /// # Arguments
+    /// * `model_id` - The id or alias of the model.
+    /// # Returns
+    /// The model.
+    /// # Errors
+    /// * `AnthropicError` - If the request fails.
+    pub async fn get_model(&self, model_id: &str) -> Result<ModelInfo, AnthropicError> {
+        self.get(&format!("{MODELS_PATH}/{model_id}"), &()).await.map(Response::into_data)
+    }
+}
@@ -0,0 +1,70 @@
+//! Options overriding the client configuration for some requests.
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderValue, IntoHeaderName};
+
+/// Options applied to the requests of a client returned by [Client::with_options], so calls with
+/// different needs can share the same client and connection pool:
+/// ```rust
+/// # use std::time::Duration;
+/// # use anthropic::client::ClientBuilder;
+/// # use anthropic::options::RequestOptions;
+/// let client = ClientBuilder::default().api_key("my-api-key".to_string()).build().unwrap();
+/// let classifier =
+///     client.with_options(RequestOptions::new().timeout(Duration::from_secs(10)).max_retries(0));
+/// let writer = client.with_options(RequestOptions::new().timeout(Duration::from_secs(600)));
+/// ```
+///
+/// The options are not an argument of every method: they configure a derived client, which keeps
+/// the API methods unchanged and lets the options be set once for a group of calls. Deriving a
+/// client is cheap, so per-call options are written `client.with_options(options).method(...)`.
+///
+/// [Client::with_options]: crate::client::Client::with_options
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RequestOptions {
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) max_retries: Option<u32>,
+    pub(crate) headers: HeaderMap,
+    pub(crate) query: Vec<(String, String)>,
+}
+
+impl RequestOptions {
+    /// No options, keeping the configuration of the client.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fail the attempts not completed after `timeout`, from sending the request to receiving the
+    /// end of the response body. For streams, the timeout covers the whole stream.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Retry the failed requests at most `max_retries` times, instead of the maximum of the retry
+    /// policy of the client. `0` disables retries.
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    /// Add a header to the requests, replacing the default one of the same name.
+    pub fn header(mut self, name: impl IntoHeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Add a query parameter to the requests.
+    pub fn query(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.query.push((key.into(), value.into()));
+        self
+    }
+
+    /// Apply `other` on top of these options.
+    pub(crate) fn merge(&mut self, other: RequestOptions) {
+        self.timeout = other.timeout.or(self.timeout);
+        self.max_retries = other.max_retries.or(self.max_retries);
+        self.headers.extend(other.headers);
+        self.query.extend(other.query);
+    }
+}
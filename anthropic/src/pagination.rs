@@ -0,0 +1,111 @@
+//! Cursor pagination of the list endpoints.
+use std::pin::Pin;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tokio_stream::Stream;
+
+use crate::client::Client;
+use crate::error::AnthropicError;
+use crate::response::Response;
+
+/// The parameters of a listing.
+/// Pages are listed after `after_id` by default, or before `before_id` when it is set.
+#[derive(Clone, Serialize, Default, Debug, Builder, PartialEq)]
+#[builder(pattern = "mutable")]
+#[builder(setter(into, strip_option), default)]
+#[builder(derive(Debug))]
+#[builder(build_fn(error = "AnthropicError"))]
+pub struct ListParams {
+    /// List the items immediately before this id.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before_id: Option<String>,
+    /// List the items immediately after this id.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after_id: Option<String>,
+    /// The number of items per page, from 1 to 1000.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+/// A page of a listing.
+#[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
+pub struct Page<T> {
+    /// The items of the page.
+    pub data: Vec<T>,
+    /// Whether there are more items in the listing direction.
+    pub has_more: bool,
+    /// The id of the first item of the page, to list the previous page with `before_id`.
+    pub first_id: Option<String>,
+    /// The id of the last item of the page, to list the next page with `after_id`.
+    pub last_id: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// The parameters listing the page following this one in the direction of `params`.
+    /// # Arguments
+    /// * `params` - The parameters this page was listed with.
+    /// # Returns
+    /// The parameters of the following page, `None` if this page is the last one.
+    pub fn next_params(&self, params: &ListParams) -> Option<ListParams> {
+        if !self.has_more {
+            return None;
+        }
+        match params.before_id {
+            Some(_) => Some(ListParams { before_id: Some(self.first_id.clone()?), after_id: None, ..params.clone() }),
+            None => Some(ListParams { before_id: None, after_id: Some(self.last_id.clone()?), ..params.clone() }),
+        }
+    }
+}
+
+/// The items of all the pages of a listing, fetched as the stream is consumed.
+pub type PageStream<T> = Pin<Box<dyn Stream<Item = Result<T, AnthropicError>> + Send>>;
+
+/// The state of a [PageStream] between two items.
+struct Pages<T> {
+    client: Client,
+    path: String,
+    /// The parameters of the next page to fetch, `None` after the last page or an error.
+    next_params: Option<ListParams>,
+    /// The items of the current page not yielded yet.
+    items: std::vec::IntoIter<T>,
+}
+
+impl Client {
+    /// List the items of {path} page after page.
+    /// # Arguments
+    /// * `path` - The path of the list endpoint.
+    /// * `params` - The parameters of the first page.
+    /// # Returns
+    /// A lazy stream of the items of all the pages, which sends no request until it is polled. It
+    /// ends after the first error.
+    pub(crate) fn paginate<T>(&self, path: &str, params: ListParams) -> PageStream<T>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        let pages = Pages {
+            client: self.clone(),
+            path: path.to_string(),
+            next_params: Some(params),
+            items: Vec::new().into_iter(),
+        };
+
+        // Fetch the next page only once the items of the current one are consumed.
+        Box::pin(futures_util::stream::unfold(pages, |mut pages| async move {
+            loop {
+                if let Some(item) = pages.items.next() {
+                    return Some((Ok(item), pages));
+                }
+                let params = pages.next_params.take()?;
+                match pages.client.get(&pages.path, &params).await.map(Response::into_data) {
+                    Ok(page) => {
+                        let page: Page<T> = page;
+                        pages.next_params = page.next_params(&params);
+                        pages.items = page.data.into_iter();
+                    }
+                    Err(e) => return Some((Err(e), pages)),
+                }
+            }
+        }))
+    }
+}
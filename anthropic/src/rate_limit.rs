@@ -0,0 +1,158 @@
+//! Rate limits reported by the API and a client-side limiter staying within them.
+//! Ref: https://docs.anthropic.com/en/api/rate-limits
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::header::HeaderMap;
+use tokio::time::Instant;
+
+/// The state of one rate limit, from its `-limit`, `-remaining` and `-reset` headers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RateLimit {
+    /// The maximum allowed in the current period.
+    pub limit: Option<u64>,
+    /// What remains before being rate limited.
+    pub remaining: Option<u64>,
+    /// When the limit is fully replenished.
+    pub reset: Option<DateTime<Utc>>,
+}
+
+impl RateLimit {
+    fn from_headers(headers: &HeaderMap, prefix: &str) -> Self {
+        let header = |suffix: &str| {
+            headers.get(format!("{prefix}-{suffix}")).and_then(|value| value.to_str().ok()).map(str::trim)
+        };
+        Self {
+            limit: header("limit").and_then(|limit| limit.parse().ok()),
+            remaining: header("remaining").and_then(|remaining| remaining.parse().ok()),
+            reset: header("reset")
+                .and_then(|reset| DateTime::parse_from_rfc3339(reset).ok())
+                .map(|reset| reset.with_timezone(&Utc)),
+        }
+    }
+
+    /// Whether nothing remains until the reset.
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining == Some(0)
+    }
+}
+
+/// The rate limits of the organization, parsed from the `anthropic-ratelimit-*` headers of a
+/// response.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RateLimitInfo {
+    /// Requests per minute.
+    pub requests: RateLimit,
+    /// Tokens per minute, the most restrictive of the input and output tokens limits.
+    pub tokens: RateLimit,
+    /// Input tokens per minute.
+    pub input_tokens: RateLimit,
+    /// Output tokens per minute.
+    pub output_tokens: RateLimit,
+}
+
+impl RateLimitInfo {
+    /// Parse the rate limits from the headers of a response.
+    /// # Arguments
+    /// * `headers` - The response headers.
+    /// # Returns
+    /// The rate limits, with `None` values for missing headers.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        Self {
+            requests: RateLimit::from_headers(headers, "anthropic-ratelimit-requests"),
+            tokens: RateLimit::from_headers(headers, "anthropic-ratelimit-tokens"),
+            input_tokens: RateLimit::from_headers(headers, "anthropic-ratelimit-input-tokens"),
+            output_tokens: RateLimit::from_headers(headers, "anthropic-ratelimit-output-tokens"),
+        }
+    }
+
+    /// The time until the latest reset of the exhausted limits, `None` if no limit is exhausted.
+    pub fn exhausted_reset_in(&self) -> Option<Duration> {
+        [&self.requests, &self.tokens, &self.input_tokens, &self.output_tokens]
+            .into_iter()
+            .filter(|limit| limit.is_exhausted())
+            .filter_map(|limit| limit.reset)
+            .max()
+            .map(|reset| (reset - Utc::now()).to_std().unwrap_or(Duration::ZERO))
+    }
+}
+
+/// A client-side token bucket limiting the rate of requests, shared by all the tasks using a
+/// [Client](crate::client::Client).
+///
+/// The bucket is also kept in sync with the [RateLimitInfo] of the responses: it never holds more
+/// requests than the API reports remaining, and it waits for the reset of exhausted limits.
+#[derive(Debug)]
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_sec: f64,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+    blocked_until: Option<Instant>,
+}
+
+impl RateLimiter {
+    /// Create a limiter allowing `requests_per_minute` requests per minute, in bursts of at most
+    /// as many requests.
+    pub fn per_minute(requests_per_minute: u32) -> Self {
+        Self::new(requests_per_minute, Duration::from_secs(60))
+    }
+
+    /// Create a limiter allowing `capacity` requests per `period`, in bursts of at most `capacity`
+    /// requests.
+    /// # Panics
+    /// If `capacity` or `period` is zero.
+    pub fn new(capacity: u32, period: Duration) -> Self {
+        assert!(capacity > 0 && !period.is_zero(), "the rate limit must allow some requests");
+        Self {
+            capacity: capacity as f64,
+            refill_per_sec: capacity as f64 / period.as_secs_f64(),
+            bucket: Mutex::new(Bucket { tokens: capacity as f64, refilled_at: Instant::now(), blocked_until: None }),
+        }
+    }
+
+    /// Wait until a request can be sent.
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().unwrap();
+                let now = Instant::now();
+                self.refill(&mut bucket, now);
+                match bucket.blocked_until {
+                    Some(until) if until > now => until - now,
+                    _ if bucket.tokens >= 1.0 => {
+                        bucket.tokens -= 1.0;
+                        return;
+                    }
+                    _ => Duration::from_secs_f64((1.0 - bucket.tokens) / self.refill_per_sec),
+                }
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Sync the bucket with the rate limits reported by the API.
+    pub fn update(&self, info: &RateLimitInfo) {
+        let mut bucket = self.bucket.lock().unwrap();
+        let now = Instant::now();
+        self.refill(&mut bucket, now);
+        if let Some(remaining) = info.requests.remaining {
+            bucket.tokens = bucket.tokens.min(remaining as f64);
+        }
+        if let Some(reset_in) = info.exhausted_reset_in() {
+            bucket.blocked_until = Some(now + reset_in);
+        }
+    }
+
+    fn refill(&self, bucket: &mut Bucket, now: Instant) {
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        bucket.refilled_at = now;
+    }
+}
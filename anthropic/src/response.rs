@@ -0,0 +1,59 @@
+//! Access to the HTTP response along with its deserialized body.
+use std::ops::Deref;
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use reqwest::header::HeaderMap;
+
+use crate::REQUEST_ID_HEADER_KEY;
+use crate::rate_limit::RateLimitInfo;
+
+/// A deserialized response body along with the metadata of the HTTP response.
+/// Dereferences to the body.
+#[derive(Debug, Clone)]
+pub struct Response<T> {
+    /// The deserialized response body.
+    pub data: T,
+    /// The HTTP status of the response.
+    pub status: StatusCode,
+    /// The headers of the response.
+    pub headers: HeaderMap,
+    /// The `request-id` header of the response, to give when contacting support.
+    pub request_id: Option<String>,
+    /// The rate limits reported in the headers of the response.
+    pub rate_limit: RateLimitInfo,
+    /// The time between sending the request and receiving the whole response body, for the
+    /// attempt that succeeded.
+    pub elapsed: Duration,
+}
+
+impl<T> Response<T> {
+    pub(crate) fn new(data: T, status: StatusCode, headers: HeaderMap, elapsed: Duration) -> Self {
+        Self {
+            data,
+            status,
+            request_id: request_id(&headers),
+            rate_limit: RateLimitInfo::from_headers(&headers),
+            headers,
+            elapsed,
+        }
+    }
+
+    /// Discard the response metadata and return the body.
+    pub fn into_data(self) -> T {
+        self.data
+    }
+}
+
+impl<T> Deref for Response<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.data
+    }
+}
+
+/// Read the `request-id` header.
+pub(crate) fn request_id(headers: &HeaderMap) -> Option<String> {
+    headers.get(REQUEST_ID_HEADER_KEY).and_then(|value| value.to_str().ok()).map(str::to_string)
+}
@@ -0,0 +1,141 @@
+//! Retry policies deciding which failed requests the client sends again.
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+
+use crate::error::AnthropicError;
+use crate::rate_limit::RateLimitInfo;
+
+/// Default maximum number of attempts of a request, including the first one.
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+/// Default longest server requested delay the client is willing to wait before retrying.
+pub const DEFAULT_MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Header with the delay before retrying, in milliseconds.
+const RETRY_AFTER_MS_HEADER_KEY: &str = "retry-after-ms";
+/// Header with which the API explicitly tells whether a request should be retried.
+const SHOULD_RETRY_HEADER_KEY: &str = "x-should-retry";
+
+/// Decides which failed requests are retried and when.
+///
+/// The delay between attempts comes from the `backoff` of the client, unless
+/// [RetryPolicy::retry_after] returns a delay requested by the server.
+pub trait RetryPolicy: fmt::Debug + Send + Sync {
+    /// The maximum number of attempts of a request, including the first one.
+    fn max_attempts(&self) -> u32;
+
+    /// Whether a request answered with a non-success `status` should be retried.
+    fn should_retry_status(&self, status: StatusCode, headers: &HeaderMap) -> bool;
+
+    /// Whether a request that failed before a response was received should be retried, `error`
+    /// being returned by the [Transport](crate::transport::Transport) of the client.
+    fn should_retry_error(&self, error: &AnthropicError) -> bool;
+
+    /// The delay requested by the server before retrying, if any.
+    fn retry_after(&self, headers: &HeaderMap) -> Option<Duration>;
+}
+
+/// The default [RetryPolicy].
+///
+/// Retries connection errors, timeouts, `AnthropicError::Transport` errors flagged as retryable,
+/// and responses with status 408, 409, 429 and 5xx (including
+/// 529 `overloaded_error`), unless the API answers with `x-should-retry: false`. Honours the
+/// `retry-after-ms` and `retry-after` headers, and the `anthropic-ratelimit-*-reset` headers of
+/// exhausted rate limits.
+#[derive(Debug, Clone)]
+pub struct DefaultRetryPolicy {
+    /// The maximum number of attempts of a request, including the first one.
+    pub max_attempts: u32,
+    /// Server requested delays longer than this are ignored in favour of the backoff interval.
+    pub max_retry_after: Duration,
+}
+
+impl Default for DefaultRetryPolicy {
+    fn default() -> Self {
+        Self { max_attempts: DEFAULT_MAX_ATTEMPTS, max_retry_after: DEFAULT_MAX_RETRY_AFTER }
+    }
+}
+
+impl RetryPolicy for DefaultRetryPolicy {
+    fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    fn should_retry_status(&self, status: StatusCode, headers: &HeaderMap) -> bool {
+        match header_str(headers, SHOULD_RETRY_HEADER_KEY) {
+            Some("true") => return true,
+            Some("false") => return false,
+            _ => {}
+        }
+        matches!(status.as_u16(), 408 | 409 | 429) || status.is_server_error()
+    }
+
+    fn should_retry_error(&self, error: &AnthropicError) -> bool {
+        match error {
+            AnthropicError::Reqwest(e) => e.is_connect() || e.is_timeout(),
+            AnthropicError::Transport { retryable, .. } => *retryable,
+            _ => false,
+        }
+    }
+
+    fn retry_after(&self, headers: &HeaderMap) -> Option<Duration> {
+        retry_after(headers).filter(|delay| *delay <= self.max_retry_after)
+    }
+}
+
+/// A [RetryPolicy] with its maximum number of attempts overridden, by
+/// [RequestOptions::max_retries](crate::options::RequestOptions::max_retries).
+#[derive(Debug)]
+pub(crate) struct MaxAttempts {
+    pub(crate) policy: Arc<dyn RetryPolicy>,
+    pub(crate) max_attempts: u32,
+}
+
+impl RetryPolicy for MaxAttempts {
+    fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    fn should_retry_status(&self, status: StatusCode, headers: &HeaderMap) -> bool {
+        self.policy.should_retry_status(status, headers)
+    }
+
+    fn should_retry_error(&self, error: &AnthropicError) -> bool {
+        self.policy.should_retry_error(error)
+    }
+
+    fn retry_after(&self, headers: &HeaderMap) -> Option<Duration> {
+        self.policy.retry_after(headers)
+    }
+}
+
+/// Parse the delay requested by the server before retrying.
+///
+/// Reads `retry-after-ms`, then `retry-after` (seconds or HTTP date), then the latest
+/// `anthropic-ratelimit-*-reset` time of the rate limits with nothing remaining.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    if let Some(ms) = header_str(headers, RETRY_AFTER_MS_HEADER_KEY).and_then(|ms| ms.parse::<f64>().ok()) {
+        return Some(Duration::from_secs_f64(ms.max(0.0) / 1000.0));
+    }
+    if let Some(retry_after) = header_str(headers, RETRY_AFTER.as_str()) {
+        if let Ok(seconds) = retry_after.parse::<f64>() {
+            return Some(Duration::from_secs_f64(seconds.max(0.0)));
+        }
+        if let Ok(date) = DateTime::parse_from_rfc2822(retry_after) {
+            return Some(until(date.with_timezone(&Utc)));
+        }
+    }
+    RateLimitInfo::from_headers(headers).exhausted_reset_in()
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, key: &str) -> Option<&'a str> {
+    headers.get(key).and_then(|value| value.to_str().ok()).map(str::trim)
+}
+
+fn until(time: DateTime<Utc>) -> Duration {
+    (time - Utc::now()).to_std().unwrap_or(Duration::ZERO)
+}
@@ -0,0 +1,151 @@
+//! Helpers to consume streamed message responses.
+use tokio_stream::StreamExt;
+
+use crate::error::AnthropicError;
+use crate::types::{ContentBlock, ContentDelta, CreateMessageResponse, CreateMessageResponseStream, StreamEvent};
+
+/// Folds the [StreamEvent]s of a streamed message into a [CreateMessageResponse].
+///
+/// The message being built is available at any moment through [MessageAccumulator::message], and
+/// once `message_stop` has been received [MessageAccumulator::finish] returns the same response
+/// `Client::create_message` would have returned.
+#[derive(Debug, Default, Clone)]
+pub struct MessageAccumulator {
+    message: Option<CreateMessageResponse>,
+    /// The `input_json_delta` fragments received for each content block, parsed when the block
+    /// stops.
+    partial_json: Vec<String>,
+    complete: bool,
+}
+
+impl MessageAccumulator {
+    /// Create an empty accumulator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Consume a whole message stream and return the final message.
+    /// # Arguments
+    /// * `stream` - The stream returned by `Client::create_message_stream`.
+    /// # Errors
+    /// * `AnthropicError` - If the stream yields an error or ends before `message_stop`.
+    pub async fn collect(mut stream: CreateMessageResponseStream) -> Result<CreateMessageResponse, AnthropicError> {
+        let mut accumulator = Self::new();
+        while let Some(event) = stream.next().await {
+            accumulator.push(&event?)?;
+        }
+        accumulator.finish()
+    }
+
+    /// Apply an event to the message being built.
+    /// # Errors
+    /// * `AnthropicError::StreamError` - If the event does not fit the message received so far.
+    pub fn push(&mut self, event: &StreamEvent) -> Result<(), AnthropicError> {
+        match event {
+            StreamEvent::MessageStart { message } => {
+                self.message = Some(message.clone().into());
+                // The message may start with content blocks, which are indexed before the new ones.
+                self.partial_json = vec![String::new(); message.content.len()];
+                self.complete = false;
+            }
+            StreamEvent::ContentBlockStart { index, content_block } => {
+                let message = self.message_mut(event)?;
+                if *index != message.content.len() {
+                    return Err(out_of_order(event));
+                }
+                message.content.push(content_block.clone());
+                self.partial_json.push(String::new());
+            }
+            StreamEvent::ContentBlockDelta { index, delta } => {
+                let message = self.message.as_mut().ok_or_else(|| out_of_order(event))?;
+                let block = message.content.get_mut(*index).ok_or_else(|| out_of_order(event))?;
+                match (block, delta) {
+                    (ContentBlock::Text { text, .. }, ContentDelta::TextDelta { text: delta }) => text.push_str(delta),
+                    (ContentBlock::Text { citations, .. }, ContentDelta::CitationsDelta { citation }) => {
+                        citations.get_or_insert_with(Vec::new).push(citation.clone())
+                    }
+                    (ContentBlock::ToolUse { .. }, ContentDelta::InputJsonDelta { partial_json }) => {
+                        let json = self.partial_json.get_mut(*index).ok_or_else(|| out_of_order(event))?;
+                        json.push_str(partial_json)
+                    }
+                    (ContentBlock::Thinking { thinking, .. }, ContentDelta::ThinkingDelta { thinking: delta }) => {
+                        thinking.push_str(delta)
+                    }
+                    (ContentBlock::Thinking { signature, .. }, ContentDelta::SignatureDelta { signature: delta }) => {
+                        *signature = delta.clone()
+                    }
+                    // Deltas this version of the SDK does not know how to apply are ignored.
+                    (ContentBlock::Unknown(_), _) | (_, ContentDelta::Unknown(_)) => {}
+                    (
+                        ContentBlock::Text { .. }
+                        | ContentBlock::Image { .. }
+                        | ContentBlock::ToolUse { .. }
+                        | ContentBlock::ToolResult { .. }
+                        | ContentBlock::Document { .. }
+                        | ContentBlock::Thinking { .. }
+                        | ContentBlock::RedactedThinking { .. },
+                        ContentDelta::TextDelta { .. }
+                        | ContentDelta::InputJsonDelta { .. }
+                        | ContentDelta::CitationsDelta { .. }
+                        | ContentDelta::ThinkingDelta { .. }
+                        | ContentDelta::SignatureDelta { .. },
+                    ) => return Err(out_of_order(event)),
+                }
+            }
+            StreamEvent::ContentBlockStop { index } => {
+                let message = self.message.as_mut().ok_or_else(|| out_of_order(event))?;
+                let block = message.content.get_mut(*index).ok_or_else(|| out_of_order(event))?;
+                let partial_json = self.partial_json.get_mut(*index).ok_or_else(|| out_of_order(event))?;
+                let partial_json = std::mem::take(partial_json);
+                if let (ContentBlock::ToolUse { input, .. }, false) = (block, partial_json.is_empty()) {
+                    *input = serde_json::from_str(&partial_json).map_err(|e| {
+                        AnthropicError::StreamError(format!("invalid tool input for content block {index}: {e}"))
+                    })?;
+                }
+            }
+            StreamEvent::MessageDelta { delta, usage } => {
+                let message = self.message_mut(event)?;
+                message.stop_reason = delta.stop_reason.clone();
+                message.stop_sequence = delta.stop_sequence.clone();
+                if let Some(usage) = usage {
+                    // The usage reported by `message_delta` is cumulative.
+                    message.usage.output_tokens = usage.output_tokens;
+                }
+            }
+            StreamEvent::MessageStop => {
+                self.message_mut(event)?;
+                self.complete = true;
+            }
+            StreamEvent::Ping | StreamEvent::Error { .. } | StreamEvent::Unknown(_) => {}
+        }
+        Ok(())
+    }
+
+    /// The message built so far, if `message_start` has been received.
+    pub fn message(&self) -> Option<&CreateMessageResponse> {
+        self.message.as_ref()
+    }
+
+    /// Whether `message_stop` has been received.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Return the final message.
+    /// # Errors
+    /// * `AnthropicError::StreamError` - If `message_stop` has not been received.
+    pub fn finish(self) -> Result<CreateMessageResponse, AnthropicError> {
+        match self.message {
+            Some(message) if self.complete => Ok(message),
+            _ => Err(AnthropicError::StreamError("stream ended before message_stop".into())),
+        }
+    }
+
+    fn message_mut(&mut self, event: &StreamEvent) -> Result<&mut CreateMessageResponse, AnthropicError> {
+        self.message.as_mut().ok_or_else(|| out_of_order(event))
+    }
+}
+
+fn out_of_order(event: &StreamEvent) -> AnthropicError {
+    AnthropicError::StreamError(format!("unexpected event: {event:?}"))
+}
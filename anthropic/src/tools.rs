@@ -0,0 +1,325 @@
+//! Tool definitions derived from Rust types, and helpers to run the tool use loop with local Rust
+//! tool handlers.
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+#[cfg(feature = "derive")]
+pub use anthropic_derive::{InputSchema, ToolInput};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value, json};
+
+use crate::client::Client;
+use crate::error::AnthropicError;
+use crate::types::{
+    Content, ContentBlock, CreateMessageRequest, CreateMessageResponse, InputMessage, StopReason, Tool,
+};
+
+/// Types whose values can be described with a JSON Schema.
+///
+/// With the `derive` feature, `#[derive(InputSchema)]` implements it for structs with named fields,
+/// using their doc comments as property descriptions.
+pub trait InputSchema {
+    /// The JSON Schema of the type.
+    fn input_schema() -> Value;
+
+    /// Whether a property of this type must be present in an object.
+    fn is_required() -> bool {
+        true
+    }
+}
+
+/// Tool inputs described by a Rust type, which provides both the tool definition and the
+/// deserialization of the `input` of `tool_use` blocks.
+///
+/// With the `derive` feature, `#[derive(ToolInput)]` implements it (along with [InputSchema]) for
+/// structs with named fields. The tool name defaults to the snake case name of the struct and can
+/// be set with `#[tool(name = "...")]`, the doc comment of the struct is the tool description.
+pub trait ToolInput: InputSchema + DeserializeOwned {
+    /// The name of the tool.
+    fn name() -> String;
+
+    /// What the tool does and when the model should use it.
+    fn description() -> Option<String> {
+        None
+    }
+
+    /// The tool definition to add to a [crate::types::CreateMessageRequest].
+    fn tool() -> Tool {
+        Tool { name: Self::name(), description: Self::description(), input_schema: Self::input_schema() }
+    }
+
+    /// Deserialize the `input` of a `tool_use` block.
+    /// # Errors
+    /// * `AnthropicError::InvalidToolInput` - If the input does not match the type.
+    fn from_input(input: Value) -> Result<Self, AnthropicError> {
+        serde_json::from_value(input).map_err(|source| AnthropicError::InvalidToolInput { tool: Self::name(), source })
+    }
+}
+
+/// Builder of the JSON Schema of an object, used by the derive macros.
+#[derive(Debug, Clone, Default)]
+pub struct ObjectSchema {
+    description: Option<String>,
+    properties: Map<String, Value>,
+    required: Vec<String>,
+}
+
+impl ObjectSchema {
+    /// Create a schema without any property.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the description of the object.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Add a property of type `T`.
+    /// It is required unless `T` is optional or `optional` is set.
+    pub fn property<T: InputSchema + ?Sized>(mut self, name: &str, description: Option<&str>, optional: bool) -> Self {
+        let mut schema = T::input_schema();
+        if let (Some(description), Value::Object(schema)) = (description, &mut schema) {
+            schema.insert("description".to_string(), description.into());
+        }
+        self.properties.insert(name.to_string(), schema);
+        if T::is_required() && !optional {
+            self.required.push(name.to_string());
+        }
+        self
+    }
+
+    /// Build the JSON Schema.
+    pub fn build(self) -> Value {
+        let mut schema = json!({"type": "object", "properties": self.properties});
+        if let Some(description) = self.description {
+            schema["description"] = description.into();
+        }
+        if !self.required.is_empty() {
+            schema["required"] = self.required.into();
+        }
+        schema
+    }
+}
+
+macro_rules! impl_input_schema {
+    ($schema_type:literal: $($ty:ty),+) => {
+        $(
+            impl InputSchema for $ty {
+                fn input_schema() -> Value {
+                    json!({"type": $schema_type})
+                }
+            }
+        )+
+    };
+}
+
+impl_input_schema!("string": String, str, char);
+impl_input_schema!("boolean": bool);
+impl_input_schema!("integer": i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+impl_input_schema!("number": f32, f64);
+
+impl<T: InputSchema> InputSchema for Option<T> {
+    fn input_schema() -> Value {
+        T::input_schema()
+    }
+
+    fn is_required() -> bool {
+        false
+    }
+}
+
+impl<T: InputSchema> InputSchema for Vec<T> {
+    fn input_schema() -> Value {
+        json!({"type": "array", "items": T::input_schema()})
+    }
+}
+
+impl<T: InputSchema> InputSchema for HashMap<String, T> {
+    fn input_schema() -> Value {
+        json!({"type": "object", "additionalProperties": T::input_schema()})
+    }
+}
+
+impl<T: InputSchema> InputSchema for BTreeMap<String, T> {
+    fn input_schema() -> Value {
+        json!({"type": "object", "additionalProperties": T::input_schema()})
+    }
+}
+
+impl<T: InputSchema + ?Sized> InputSchema for Box<T> {
+    fn input_schema() -> Value {
+        T::input_schema()
+    }
+
+    fn is_required() -> bool {
+        T::is_required()
+    }
+}
+
+impl InputSchema for Value {
+    fn input_schema() -> Value {
+        json!({})
+    }
+}
+
+/// Default maximum number of messages the [ToolRunner] sends before giving up.
+pub const DEFAULT_MAX_ITERATIONS: usize = 10;
+
+type ToolFuture = Pin<Box<dyn Future<Output = Result<Content, String>> + Send>>;
+type ToolHandler = Arc<dyn Fn(Value) -> ToolFuture + Send + Sync>;
+
+/// Runs the tool use loop: send the conversation, execute the requested tools with the registered
+/// handlers, send their results back, until the model stops asking for tools.
+#[derive(Clone)]
+pub struct ToolRunner {
+    tools: Vec<Tool>,
+    handlers: HashMap<String, ToolHandler>,
+    max_iterations: usize,
+}
+
+/// The outcome of [ToolRunner::run].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolRunOutput {
+    /// The last response, which does not ask for any tool.
+    pub response: CreateMessageResponse,
+    /// The whole conversation, from the messages of the initial request to the final response.
+    pub messages: Vec<InputMessage>,
+}
+
+impl ToolRunner {
+    /// Create a runner without any tool.
+    pub fn new() -> Self {
+        Self { tools: Vec::new(), handlers: HashMap::new(), max_iterations: DEFAULT_MAX_ITERATIONS }
+    }
+
+    /// Set the maximum number of messages sent before failing with
+    /// [AnthropicError::MaxIterations].
+    pub fn max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// Register a tool and the handler executing it.
+    /// The handler receives the `input` of the `tool_use` block. Its output is sent back as the
+    /// tool result, and its errors as a tool result with `is_error` set.
+    pub fn register<F, Fut, C, E>(mut self, tool: Tool, handler: F) -> Self
+    where
+        F: Fn(Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<C, E>> + Send + 'static,
+        C: Into<Content>,
+        E: fmt::Display,
+    {
+        let handler: ToolHandler = Arc::new(move |input| {
+            let future = handler(input);
+            Box::pin(async move { future.await.map(Into::into).map_err(|e| e.to_string()) })
+        });
+        self.tools.retain(|registered| registered.name != tool.name);
+        self.handlers.insert(tool.name.clone(), handler);
+        self.tools.push(tool);
+        self
+    }
+
+    /// Register a tool described by a [ToolInput] type and the handler executing it.
+    /// Inputs that do not deserialize into `T` are reported to the model as failed tool results
+    /// without calling the handler.
+    pub fn register_input<T, F, Fut, C, E>(self, handler: F) -> Self
+    where
+        T: ToolInput + Send + 'static,
+        F: Fn(T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<C, E>> + Send + 'static,
+        C: Into<Content> + 'static,
+        E: fmt::Display + 'static,
+    {
+        let handler = Arc::new(handler);
+        self.register(T::tool(), move |input| {
+            let handler = handler.clone();
+            async move {
+                let input = T::from_input(input).map_err(|e| e.to_string())?;
+                handler(input).await.map_err(|e| e.to_string())
+            }
+        })
+    }
+
+    /// The definitions of the registered tools.
+    pub fn tools(&self) -> &[Tool] {
+        &self.tools
+    }
+
+    /// Send `request` and keep executing the requested tools until the model ends its turn.
+    /// The registered tools are added to the tools of the request.
+    /// # Arguments
+    /// * `client` - The client used to send the messages.
+    /// * `request` - The initial request.
+    /// # Errors
+    /// * `AnthropicError::MaxIterations` - If the model still asks for tools after the maximum
+    ///   number of iterations, with the conversation so far.
+    /// * `AnthropicError` - If a request fails.
+    pub async fn run(
+        &self,
+        client: &Client,
+        mut request: CreateMessageRequest,
+    ) -> Result<ToolRunOutput, AnthropicError> {
+        let tools = request.tools.get_or_insert_with(Vec::new);
+        for tool in &self.tools {
+            if !tools.iter().any(|t| t.name == tool.name) {
+                tools.push(tool.clone());
+            }
+        }
+
+        for _ in 0..self.max_iterations {
+            let response = client.create_message(request.clone()).await?;
+            request.messages.push(InputMessage::assistant(response.content.clone()));
+
+            let tool_uses: Vec<_> = response
+                .content
+                .iter()
+                .filter_map(|block| match block {
+                    ContentBlock::ToolUse { id, name, input } => Some((id, name, input)),
+                    _ => None,
+                })
+                .collect();
+            // A `tool_use` stop reason without any tool to call has nothing to answer.
+            if response.stop_reason != Some(StopReason::ToolUse) || tool_uses.is_empty() {
+                return Ok(ToolRunOutput { response, messages: request.messages });
+            }
+
+            let mut results = Vec::new();
+            for (id, name, input) in tool_uses {
+                results.push(self.call(id, name, input.clone()).await);
+            }
+            request.messages.push(InputMessage::user(results));
+        }
+
+        Err(AnthropicError::MaxIterations { iterations: self.max_iterations, messages: request.messages })
+    }
+
+    async fn call(&self, id: &str, name: &str, input: Value) -> ContentBlock {
+        let Some(handler) = self.handlers.get(name) else {
+            return ContentBlock::tool_error(id, format!("unknown tool: {name}"));
+        };
+        match handler(input).await {
+            Ok(content) => ContentBlock::tool_result(id, content),
+            Err(e) => ContentBlock::tool_error(id, e),
+        }
+    }
+}
+
+impl Default for ToolRunner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for ToolRunner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ToolRunner")
+            .field("tools", &self.tools)
+            .field("max_iterations", &self.max_iterations)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,146 @@
+//! The HTTP layer of the client, replaceable to test without a server, replay recorded responses or
+//! use another HTTP stack.
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+
+use bytes::Bytes;
+use eventsource_stream::{EventStreamError, Eventsource};
+use reqwest::StatusCode;
+use reqwest::header::{ACCEPT, HeaderMap, HeaderValue};
+use tokio_stream::{Stream, StreamExt};
+
+use crate::error::AnthropicError;
+
+/// The response of a [Transport], once its headers are received.
+pub type TransportFuture<'a> = Pin<Box<dyn Future<Output = Result<HttpResponse, AnthropicError>> + Send + 'a>>;
+
+/// A response body, received chunk by chunk.
+pub type ByteStream = Pin<Box<dyn Stream<Item = Result<Bytes, AnthropicError>> + Send>>;
+
+/// The Server-Sent Events of a response body.
+pub type ServerSentEventStream = Pin<Box<dyn Stream<Item = Result<ServerSentEvent, AnthropicError>> + Send>>;
+
+/// Sends the HTTP requests of a [Client](crate::client::Client).
+///
+/// The client builds the requests, with their url, headers, body and timeout, and handles the
+/// responses: retries, rate limits, API errors, deserialization and the parsing of the streams.
+///
+/// The requests are plain `reqwest::Request` values, which other HTTP stacks can read with
+/// [reqwest::Request::method], [reqwest::Request::url], [reqwest::Request::headers],
+/// [reqwest::Request::body] and [reqwest::Request::timeout], without using a `reqwest::Client`.
+/// The bodies are buffered, and read with [reqwest::Body::as_bytes], except for the uploads of
+/// [Client::upload_file_reader](crate::client::Client::upload_file_reader), which are streamed and
+/// not supported by custom transports.
+pub trait Transport: fmt::Debug + Send + Sync {
+    /// Send a request and return its response, whatever its status.
+    /// # Arguments
+    /// * `request` - The request to send.
+    /// # Returns
+    /// The response, with its body left to be received.
+    /// # Errors
+    /// * `AnthropicError` - If no response is received, e.g. `AnthropicError::Transport`. Whether
+    ///   the request is sent again is decided by the retry policy of the client.
+    fn send(&self, request: reqwest::Request) -> TransportFuture<'_>;
+
+    /// Send a request answered with a stream of Server-Sent Events, parsed by the client from the
+    /// body of the successful responses. Defaults to [Transport::send] with the
+    /// `accept: text/event-stream` header.
+    /// # Arguments
+    /// * `request` - The request to send.
+    /// # Returns
+    /// The response, with its events left to be received.
+    /// # Errors
+    /// * `AnthropicError` - If no response is received.
+    fn open_event_stream(&self, mut request: reqwest::Request) -> TransportFuture<'_> {
+        request.headers_mut().insert(ACCEPT, HeaderValue::from_static("text/event-stream"));
+        self.send(request)
+    }
+}
+
+/// The default [Transport], sending the requests with a `reqwest::Client`.
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    /// Send the requests with `client`.
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(&self, request: reqwest::Request) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let response = self.client.execute(request).await?;
+            let status = response.status();
+            let headers = response.headers().clone();
+            let body = response.bytes_stream().map(|chunk| chunk.map_err(AnthropicError::Reqwest));
+
+            Ok(HttpResponse::from_stream(status, headers, Box::pin(body)))
+        })
+    }
+}
+
+/// An HTTP response returned by a [Transport].
+pub struct HttpResponse {
+    /// The HTTP status of the response.
+    pub status: StatusCode,
+    /// The headers of the response.
+    pub headers: HeaderMap,
+    /// The body of the response.
+    pub body: ByteStream,
+}
+
+impl HttpResponse {
+    /// A response with a body already received, e.g. built by an in-memory transport.
+    pub fn new(status: StatusCode, headers: HeaderMap, body: impl Into<Bytes>) -> Self {
+        let body = body.into();
+        Self::from_stream(status, headers, Box::pin(tokio_stream::once(Ok(body))))
+    }
+
+    /// A response with a body received chunk by chunk.
+    pub fn from_stream(status: StatusCode, headers: HeaderMap, body: ByteStream) -> Self {
+        Self { status, headers, body }
+    }
+
+    /// Receive the whole body.
+    /// # Errors
+    /// * `AnthropicError` - If the body cannot be received.
+    pub async fn bytes(mut self) -> Result<Bytes, AnthropicError> {
+        let mut bytes = Vec::new();
+        while let Some(chunk) = self.body.next().await {
+            bytes.extend_from_slice(&chunk?);
+        }
+        Ok(Bytes::from(bytes))
+    }
+
+    /// Parse the body as Server-Sent Events, as they are received.
+    pub fn events(self) -> ServerSentEventStream {
+        Box::pin(self.body.eventsource().map(|event| match event {
+            Ok(event) => Ok(ServerSentEvent { event: event.event, data: event.data }),
+            Err(EventStreamError::Transport(e)) => Err(e),
+            Err(e) => Err(AnthropicError::StreamError(e.to_string())),
+        }))
+    }
+}
+
+impl fmt::Debug for HttpResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpResponse")
+            .field("status", &self.status)
+            .field("headers", &self.headers)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A Server-Sent Event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerSentEvent {
+    /// The name of the event, `message` when the server does not name it.
+    pub event: String,
+    /// The data of the event.
+    pub data: String,
+}
@@ -1,12 +1,18 @@
 //! Module for types used in the API.
+use std::path::Path;
 use std::pin::Pin;
 
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
 use derive_builder::Builder;
-use serde::{Deserialize, Serialize};
+use reqwest::header::HeaderMap;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use tokio_stream::Stream;
 
+use crate::DEFAULT_COMPLETION_MODEL;
+use crate::beta::Beta;
 use crate::error::AnthropicError;
-use crate::DEFAULT_MODEL;
 
 #[derive(Clone, Serialize, Default, Debug, Builder, PartialEq)]
 #[builder(pattern = "mutable")]
@@ -14,16 +20,162 @@ use crate::DEFAULT_MODEL;
 #[builder(derive(Debug))]
 #[builder(build_fn(error = "AnthropicError"))]
 pub struct CreateMessageRequest {
+    /// The model to use. Left empty, the `default_model` of the client is used.
     pub model: String,
-    pub messages: Vec<Message>,
+    /// The conversation so far, alternating between `user` and `assistant` turns.
+    pub messages: Vec<InputMessage>,
+    /// The system prompt.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub system: Option<String>,
+    /// The maximum number of tokens to generate before stopping.
     pub max_tokens: i32,
+    /// The stop sequences to use.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub stop_sequences: Option<Vec<String>>,
+    /// Whether to incrementally stream the response.
     #[builder(default = "false")]
     pub stream: bool,
+    /// The amount of randomness injected into the response.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f64>,
+    /// Use nucleus sampling.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub top_p: Option<f64>,
+    /// Only sample from the top K options for each subsequent token.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub top_k: Option<i32>,
+    /// Definitions of the tools the model may use.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<Tool>>,
+    /// How the model should use the provided tools.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<ToolChoice>,
+    /// The beta features enabled for this request, in addition to the ones of the client. To
+    /// replace the betas of the client, set the `anthropic-beta` header in `extra_headers`.
+    #[serde(skip)]
+    pub betas: Vec<Beta>,
+    /// The headers added to this request, replacing the ones of the client with the same name.
+    #[serde(skip)]
+    pub extra_headers: HeaderMap,
+    /// The `idempotency-key` header of this request, for the API to not process it twice when it
+    /// is retried. Different requests must not share a key.
+    #[serde(skip)]
+    pub idempotency_key: Option<String>,
+    /// The fields added to the request body, for the parameters not supported by the SDK yet.
+    #[serde(flatten)]
+    pub extra_body: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A request counting the input tokens of a message, without creating it.
+#[derive(Clone, Serialize, Default, Debug, Builder, PartialEq)]
+#[builder(pattern = "mutable")]
+#[builder(setter(into, strip_option), default)]
+#[builder(derive(Debug))]
+#[builder(build_fn(error = "AnthropicError"))]
+pub struct CountTokensRequest {
+    /// The model to use. Left empty, the `default_model` of the client is used.
+    pub model: String,
+    /// The conversation so far, alternating between `user` and `assistant` turns.
+    pub messages: Vec<InputMessage>,
+    /// The system prompt.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+    /// Definitions of the tools the model may use.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<Tool>>,
+    /// How the model should use the provided tools.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<ToolChoice>,
+    /// The beta features enabled for this request, in addition to the ones of the client. To
+    /// replace the betas of the client, set the `anthropic-beta` header in `extra_headers`.
+    #[serde(skip)]
+    pub betas: Vec<Beta>,
+    /// The headers added to this request, replacing the ones of the client with the same name.
+    #[serde(skip)]
+    pub extra_headers: HeaderMap,
+    /// The fields added to the request body, for the parameters not supported by the SDK yet.
+    #[serde(flatten)]
+    pub extra_body: serde_json::Map<String, serde_json::Value>,
+}
+
+impl From<CreateMessageRequest> for CountTokensRequest {
+    /// Count the input tokens of a message request.
+    fn from(request: CreateMessageRequest) -> Self {
+        Self {
+            model: request.model,
+            messages: request.messages,
+            system: request.system,
+            tools: request.tools,
+            tool_choice: request.tool_choice,
+            betas: request.betas,
+            extra_headers: request.extra_headers,
+            extra_body: request.extra_body,
+        }
+    }
+}
+
+/// The number of input tokens of a [CountTokensRequest].
+#[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
+pub struct CountTokensResponse {
+    /// The total number of tokens of the messages, system prompt and tools.
+    pub input_tokens: i32,
+}
+
+/// Definition of a tool the model may use.
+#[derive(Clone, Serialize, Deserialize, Default, Debug, Builder, PartialEq)]
+#[builder(pattern = "mutable")]
+#[builder(setter(into, strip_option), default)]
+#[builder(derive(Debug))]
+#[builder(build_fn(error = "AnthropicError"))]
+pub struct Tool {
+    /// The name of the tool, referenced by `tool_use` blocks.
+    pub name: String,
+    /// What the tool does and when the model should use it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The JSON Schema of the tool input.
+    pub input_schema: serde_json::Value,
+}
+
+/// How the model should use the tools of a [CreateMessageRequest].
+#[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToolChoice {
+    /// The model decides whether to use a tool.
+    Auto,
+    /// The model must use one of the tools.
+    Any,
+    /// The model must use the named tool.
+    Tool { name: String },
+    /// The model must not use any tool.
+    None,
+}
+
+/// The role of the author of a message.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    User,
+    Assistant,
+}
+
+/// A message sent to the Messages API as part of a [CreateMessageRequest].
+#[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
+pub struct InputMessage {
+    pub role: Role,
+    pub content: Content,
+}
+
+impl InputMessage {
+    /// Create a `user` message.
+    pub fn user(content: impl Into<Content>) -> Self {
+        Self { role: Role::User, content: content.into() }
+    }
+
+    /// Create an `assistant` message.
+    pub fn assistant(content: impl Into<Content>) -> Self {
+        Self { role: Role::Assistant, content: content.into() }
+    }
 }
 
 #[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
@@ -31,10 +183,10 @@ pub struct Message {
     pub id: String,
     #[serde(rename = "type")]
     pub object_type: String, // Always "message"
-    pub role: String, // Always "assistant"
+    pub role: Role, // Always "assistant"
     pub content: Vec<ContentBlock>,
     pub model: String,
-    pub stop_reason: Option<String>,
+    pub stop_reason: Option<StopReason>,
     pub stop_sequence: Option<String>,
     pub usage: Usage,
 }
@@ -46,23 +198,374 @@ pub enum Content {
     Blocks(Vec<ContentBlock>),
 }
 
+impl From<String> for Content {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<&str> for Content {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
+}
+
+impl From<Vec<ContentBlock>> for Content {
+    fn from(blocks: Vec<ContentBlock>) -> Self {
+        Self::Blocks(blocks)
+    }
+}
+
+/// A value whose `type` is unknown to this version of the SDK, kept as raw JSON and serialized back
+/// as is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownType {
+    /// The `type` of the value.
+    pub kind: String,
+    /// The whole value, including its `type`.
+    pub raw: serde_json::Value,
+}
+
+impl Serialize for UnknownType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.raw.serialize(serializer)
+    }
+}
+
+/// The `type` tags of the variants of an enum with an [UnknownType] fallback.
+pub(crate) trait KnownTypes {
+    const TYPES: &'static [&'static str];
+}
+
+/// Deserialize the [UnknownType] fallback of `T`. Values of a known type only reach the fallback
+/// when they are malformed, which is an error rather than an unknown value.
+pub(crate) fn unknown_type<'de, D, T>(deserializer: D) -> Result<UnknownType, D::Error>
+where
+    D: Deserializer<'de>,
+    T: KnownTypes,
+{
+    let raw = serde_json::Value::deserialize(deserializer)?;
+    let Some(kind) = raw.get("type").and_then(serde_json::Value::as_str) else {
+        return Err(D::Error::custom("missing field `type`"));
+    };
+    if T::TYPES.contains(&kind) {
+        return Err(D::Error::custom(format!("invalid `{kind}` value")));
+    }
+    Ok(UnknownType { kind: kind.to_string(), raw })
+}
+
 #[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
-pub struct ContentBlock {
-    #[serde(rename = "type")]
-    pub content_type: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub text: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub source: Option<ImageSource>,
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    Text {
+        text: String,
+        /// The passages of the documents supporting the text, when citations are enabled.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        citations: Option<Vec<Citation>>,
+    },
+    Image {
+        source: ImageSource,
+    },
+    /// A request from the model to use a tool.
+    ToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    /// The result of a tool use, sent back in a `user` message.
+    ToolResult {
+        tool_use_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        content: Option<Content>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        is_error: Option<bool>,
+    },
+    /// A document provided to the model.
+    Document {
+        source: DocumentSource,
+        /// The title of the document, which the model can cite.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        /// Context about the document, which the model does not cite.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        context: Option<String>,
+        /// Whether the model should cite the document.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        citations: Option<CitationsConfig>,
+    },
+    /// The reasoning of the model when extended thinking is enabled.
+    Thinking {
+        thinking: String,
+        /// Empty in the `content_block_start` event of a stream, sent later in a `signature_delta`.
+        #[serde(default)]
+        signature: String,
+    },
+    /// Reasoning flagged by the safety systems, returned encrypted.
+    RedactedThinking {
+        data: String,
+    },
+    /// A block type unknown to this version of the SDK, kept as raw JSON.
+    #[serde(untagged, deserialize_with = "unknown_type::<_, ContentBlock>")]
+    Unknown(UnknownType),
 }
 
+impl KnownTypes for ContentBlock {
+    const TYPES: &'static [&'static str] =
+        &["text", "image", "tool_use", "tool_result", "document", "thinking", "redacted_thinking"];
+}
+
+impl ContentBlock {
+    /// Create a text block.
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::Text { text: text.into(), citations: None }
+    }
+
+    /// Create a successful tool result block.
+    pub fn tool_result(tool_use_id: impl Into<String>, content: impl Into<Content>) -> Self {
+        Self::ToolResult { tool_use_id: tool_use_id.into(), content: Some(content.into()), is_error: None }
+    }
+
+    /// Create a failed tool result block.
+    pub fn tool_error(tool_use_id: impl Into<String>, content: impl Into<Content>) -> Self {
+        Self::ToolResult { tool_use_id: tool_use_id.into(), content: Some(content.into()), is_error: Some(true) }
+    }
+
+    /// Create an image block.
+    pub fn image(source: ImageSource) -> Self {
+        Self::Image { source }
+    }
+
+    /// Create a document block, without title, context or citations.
+    pub fn document(source: DocumentSource) -> Self {
+        Self::Document { source, title: None, context: None, citations: None }
+    }
+
+    /// Create a document block whose passages the model cites in its answer.
+    pub fn cited_document(source: DocumentSource, title: impl Into<String>) -> Self {
+        Self::Document {
+            source,
+            title: Some(title.into()),
+            context: None,
+            citations: Some(CitationsConfig { enabled: true }),
+        }
+    }
+
+    /// The text of a text block.
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Self::Text { text, .. } => Some(text),
+            _ => None,
+        }
+    }
+}
+
+impl From<ImageSource> for ContentBlock {
+    fn from(source: ImageSource) -> Self {
+        Self::image(source)
+    }
+}
+
+/// Maximum size of the base64 encoded data of an image accepted by the API, in bytes.
+pub const MAX_IMAGE_SIZE: usize = 5 * 1024 * 1024;
+
 #[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
-pub struct ImageSource {
-    #[serde(rename = "type")]
-    pub source_type: String,
-    #[serde(rename = "media_type")]
-    pub media_type: String,
-    pub data: String,
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ImageSource {
+    /// Base64 encoded image data.
+    Base64 { media_type: ImageMediaType, data: String },
+    /// An image fetched by the API from a URL.
+    Url { url: String },
+    /// An image uploaded with the Files API.
+    File { file_id: String },
+    /// A source type unknown to this version of the SDK, kept as raw JSON.
+    #[serde(untagged, deserialize_with = "unknown_type::<_, ImageSource>")]
+    Unknown(UnknownType),
+}
+
+impl KnownTypes for ImageSource {
+    const TYPES: &'static [&'static str] = &["base64", "url", "file"];
+}
+
+impl ImageSource {
+    /// Read an image file.
+    /// The media type is detected from the content of the file.
+    /// # Errors
+    /// * `AnthropicError::Io` - If the file cannot be read.
+    /// * `AnthropicError::InvalidArgument` - If the image is not supported by the API.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, AnthropicError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// Encode image bytes.
+    /// The media type is detected from the content of the image.
+    /// # Errors
+    /// * `AnthropicError::InvalidArgument` - If the image is not a JPEG, PNG, GIF or WebP image, or
+    ///   is larger than [MAX_IMAGE_SIZE] once base64 encoded.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, AnthropicError> {
+        let media_type = ImageMediaType::sniff(bytes).ok_or_else(|| {
+            AnthropicError::InvalidArgument("unsupported image format, expected JPEG, PNG, GIF or WebP".into())
+        })?;
+        let encoded_len = 4 * bytes.len().div_ceil(3);
+        if encoded_len > MAX_IMAGE_SIZE {
+            return Err(AnthropicError::InvalidArgument(format!(
+                "image is {encoded_len} bytes once base64 encoded, the maximum is {MAX_IMAGE_SIZE} bytes"
+            )));
+        }
+        Ok(Self::Base64 { media_type, data: BASE64.encode(bytes) })
+    }
+
+    /// Reference an image by URL.
+    pub fn from_url(url: impl Into<String>) -> Self {
+        Self::Url { url: url.into() }
+    }
+
+    /// Reference an image uploaded with the Files API. The message request must be sent with the
+    /// [files beta](crate::beta::Beta::FilesApi).
+    pub fn from_file_id(file_id: impl Into<String>) -> Self {
+        Self::File { file_id: file_id.into() }
+    }
+}
+
+/// The image formats supported by the API.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ImageMediaType {
+    #[serde(rename = "image/jpeg")]
+    Jpeg,
+    #[serde(rename = "image/png")]
+    Png,
+    #[serde(rename = "image/gif")]
+    Gif,
+    #[serde(rename = "image/webp")]
+    Webp,
+}
+
+impl ImageMediaType {
+    /// Detect the format of an image from its magic bytes.
+    pub fn sniff(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [0xFF, 0xD8, 0xFF, ..] => Some(Self::Jpeg),
+            [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, ..] => Some(Self::Png),
+            [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => Some(Self::Gif),
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some(Self::Webp),
+            _ => None,
+        }
+    }
+
+    /// The MIME type of the format.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Jpeg => "image/jpeg",
+            Self::Png => "image/png",
+            Self::Gif => "image/gif",
+            Self::Webp => "image/webp",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DocumentSource {
+    /// Base64 encoded document data, e.g. a PDF.
+    Base64 { media_type: String, data: String },
+    /// Plain text.
+    Text { media_type: String, data: String },
+    /// Custom content, each text block being a citable chunk.
+    Content { content: Content },
+    /// A PDF fetched by the API from a URL.
+    Url { url: String },
+    /// A document uploaded with the Files API.
+    File { file_id: String },
+    /// A source type unknown to this version of the SDK, kept as raw JSON.
+    #[serde(untagged, deserialize_with = "unknown_type::<_, DocumentSource>")]
+    Unknown(UnknownType),
+}
+
+impl KnownTypes for DocumentSource {
+    const TYPES: &'static [&'static str] = &["base64", "text", "content", "url", "file"];
+}
+
+impl DocumentSource {
+    /// Read a PDF file.
+    /// # Errors
+    /// * `AnthropicError::Io` - If the file cannot be read.
+    /// * `AnthropicError::InvalidArgument` - If the file is not a PDF.
+    pub fn pdf_from_path(path: impl AsRef<Path>) -> Result<Self, AnthropicError> {
+        Self::pdf(&std::fs::read(path)?)
+    }
+
+    /// Encode a PDF document.
+    /// # Errors
+    /// * `AnthropicError::InvalidArgument` - If the bytes are not a PDF.
+    pub fn pdf(bytes: &[u8]) -> Result<Self, AnthropicError> {
+        if !bytes.starts_with(b"%PDF-") {
+            return Err(AnthropicError::InvalidArgument("document is not a PDF".into()));
+        }
+        Ok(Self::Base64 { media_type: "application/pdf".to_string(), data: BASE64.encode(bytes) })
+    }
+
+    /// A plain text document.
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::Text { media_type: "text/plain".to_string(), data: text.into() }
+    }
+
+    /// A document made of custom chunks, cited by block index.
+    pub fn content(content: impl Into<Content>) -> Self {
+        Self::Content { content: content.into() }
+    }
+
+    /// Reference a PDF by URL.
+    pub fn from_url(url: impl Into<String>) -> Self {
+        Self::Url { url: url.into() }
+    }
+
+    /// Reference a document uploaded with the Files API. The message request must be sent with
+    /// the [files beta](crate::beta::Beta::FilesApi).
+    pub fn from_file_id(file_id: impl Into<String>) -> Self {
+        Self::File { file_id: file_id.into() }
+    }
+}
+
+/// Enables citations on a document.
+#[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
+pub struct CitationsConfig {
+    pub enabled: bool,
+}
+
+/// A passage of a document supporting a text block.
+#[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Citation {
+    /// A range of characters of a plain text document.
+    CharLocation {
+        cited_text: String,
+        document_index: usize,
+        document_title: Option<String>,
+        start_char_index: usize,
+        end_char_index: usize,
+    },
+    /// A range of pages of a PDF document, numbered from 1.
+    PageLocation {
+        cited_text: String,
+        document_index: usize,
+        document_title: Option<String>,
+        start_page_number: usize,
+        end_page_number: usize,
+    },
+    /// A range of blocks of a custom content document.
+    ContentBlockLocation {
+        cited_text: String,
+        document_index: usize,
+        document_title: Option<String>,
+        start_block_index: usize,
+        end_block_index: usize,
+    },
+    /// A citation type unknown to this version of the SDK, kept as raw JSON.
+    #[serde(untagged, deserialize_with = "unknown_type::<_, Citation>")]
+    Unknown(UnknownType),
+}
+
+impl KnownTypes for Citation {
+    const TYPES: &'static [&'static str] = &["char_location", "page_location", "content_block_location"];
 }
 
 #[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
@@ -70,63 +573,100 @@ pub struct CreateMessageResponse {
     pub id: String,
     #[serde(rename = "type")]
     pub object_type: String, // Always "message"
-    pub role: String, // Always "assistant"
+    pub role: Role, // Always "assistant"
     pub content: Vec<ContentBlock>,
     pub model: String,
-    pub stop_reason: String,
+    pub stop_reason: Option<StopReason>,
     pub stop_sequence: Option<String>,
     pub usage: Usage,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+impl From<Message> for CreateMessageResponse {
+    fn from(message: Message) -> Self {
+        Self {
+            id: message.id,
+            object_type: message.object_type,
+            role: message.role,
+            content: message.content,
+            model: message.model,
+            stop_reason: message.stop_reason,
+            stop_sequence: message.stop_sequence,
+            usage: message.usage,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(tag = "type")]
 pub enum StreamEvent {
     #[serde(rename = "message_start")]
-    MessageStart {
-        message: Message,
-    },
+    MessageStart { message: Message },
     #[serde(rename = "content_block_start")]
-    ContentBlockStart {
-        index: usize,
-        content_block: ContentBlock,
-    },
+    ContentBlockStart { index: usize, content_block: ContentBlock },
     #[serde(rename = "content_block_delta")]
-    ContentBlockDelta {
-        index: usize,
-        delta: ContentDelta,
-    },
+    ContentBlockDelta { index: usize, delta: ContentDelta },
     #[serde(rename = "content_block_stop")]
-    ContentBlockStop {
-        index: usize,
-    },
+    ContentBlockStop { index: usize },
     #[serde(rename = "message_delta")]
-    MessageDelta {
-        delta: MessageDelta,
-    },
+    MessageDelta { delta: MessageDelta, usage: Option<MessageDeltaUsage> },
     #[serde(rename = "message_stop")]
     MessageStop,
     #[serde(rename = "ping")]
     Ping,
     #[serde(rename = "error")]
-    Error {
-        error: ErrorData,
-    },
-    // Fallback for unknown events
-    Unknown {},
+    Error { error: ErrorData },
+    /// An event type unknown to this version of the SDK, kept as raw JSON.
+    #[serde(untagged, deserialize_with = "unknown_type::<_, StreamEvent>")]
+    Unknown(UnknownType),
+}
+
+impl KnownTypes for StreamEvent {
+    const TYPES: &'static [&'static str] = &[
+        "message_start",
+        "content_block_start",
+        "content_block_delta",
+        "content_block_stop",
+        "message_delta",
+        "message_stop",
+        "ping",
+        "error",
+    ];
 }
 
 #[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
-pub struct ContentDelta {
-    #[serde(rename = "type")]
-    pub delta_type: String, // Currently, can be "text_delta"
-    pub text: String,
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentDelta {
+    /// Text appended to a `text` block.
+    TextDelta { text: String },
+    /// A fragment of the JSON input of a `tool_use` block.
+    /// The fragments form valid JSON once the block is complete.
+    InputJsonDelta { partial_json: String },
+    /// A citation added to a `text` block.
+    CitationsDelta { citation: Citation },
+    /// Reasoning appended to a `thinking` block.
+    ThinkingDelta { thinking: String },
+    /// The signature of a `thinking` block, sent just before the block stops.
+    SignatureDelta { signature: String },
+    /// A delta type unknown to this version of the SDK, kept as raw JSON.
+    #[serde(untagged, deserialize_with = "unknown_type::<_, ContentDelta>")]
+    Unknown(UnknownType),
+}
+
+impl KnownTypes for ContentDelta {
+    const TYPES: &'static [&'static str] =
+        &["text_delta", "input_json_delta", "citations_delta", "thinking_delta", "signature_delta"];
 }
 
 #[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
 pub struct MessageDelta {
-    pub stop_reason: Option<String>,
+    pub stop_reason: Option<StopReason>,
     pub stop_sequence: Option<String>,
-    pub usage: Option<Usage>,
+}
+
+/// Cumulative token usage reported by a `message_delta` event.
+#[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
+pub struct MessageDeltaUsage {
+    pub output_tokens: i32,
 }
 
 #[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
@@ -151,7 +691,7 @@ pub struct CompleteRequest {
     /// The prompt to complete.
     pub prompt: String,
     /// The model to use.
-    #[builder(default = "DEFAULT_MODEL.to_string()")]
+    #[builder(default = "DEFAULT_COMPLETION_MODEL.to_string()")]
     pub model: String,
     /// The number of tokens to sample.
     pub max_tokens_to_sample: usize,
@@ -168,15 +708,29 @@ pub struct CompleteResponse {
     pub stop_reason: Option<StopReason>,
 }
 
-/// Parsed server side events stream until a [StopReason::StopSequence] is received from server.
+/// Parsed server side events stream until a [StreamEvent::MessageStop] is received from server.
 pub type CreateMessageResponseStream = Pin<Box<dyn Stream<Item = Result<StreamEvent, AnthropicError>> + Send>>;
 
 /// Parsed server side events stream until a [StopReason::StopSequence] is received from server.
 pub type CompleteResponseStream = Pin<Box<dyn Stream<Item = Result<CompleteResponse, AnthropicError>> + Send>>;
 
-#[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
+/// Why the model stopped generating.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum StopReason {
+    /// The model reached a natural stopping point.
+    EndTurn,
+    /// The requested `max_tokens` was reached.
     MaxTokens,
+    /// One of the stop sequences was generated.
     StopSequence,
+    /// The model asks for one or more tools to be used.
+    ToolUse,
+    /// A long running turn was paused and can be continued.
+    PauseTurn,
+    /// The model declined to answer.
+    Refusal,
+    /// A stop reason unknown to this version of the SDK.
+    #[serde(untagged)]
+    Unknown(String),
 }
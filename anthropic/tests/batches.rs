@@ -0,0 +1,324 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anthropic::batches::{
+    CreateMessageBatchRequest, MessageBatchIndividualResponse, MessageBatchRequest, MessageBatchResult,
+    ProcessingStatus, RequestCounts, WaitForBatchOptions,
+};
+use anthropic::beta::Beta;
+use anthropic::error::{AnthropicError, ApiErrorKind};
+use anthropic::pagination::ListParamsBuilder;
+use anthropic::types::{CreateMessageRequest, CreateMessageRequestBuilder, InputMessage};
+use common::client;
+use reqwest::header::{HeaderMap, HeaderValue};
+use serde_json::{Value, json};
+use tokio_stream::StreamExt;
+use wiremock::matchers::{body_json, body_partial_json, header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn request(text: &str) -> CreateMessageRequest {
+    CreateMessageRequestBuilder::default()
+        .model("claude-3-haiku-20240307")
+        .messages(vec![InputMessage::user(text)])
+        .max_tokens(16)
+        .build()
+        .unwrap()
+}
+
+fn batch(id: &str, processing_status: &str) -> Value {
+    json!({
+        "id": id,
+        "type": "message_batch",
+        "processing_status": processing_status,
+        "request_counts": {"processing": 1, "succeeded": 1, "errored": 0, "canceled": 0, "expired": 0},
+        "ended_at": null,
+        "created_at": "2024-09-24T18:37:24.100435Z",
+        "expires_at": "2024-09-25T18:37:24.100435Z",
+        "archived_at": null,
+        "cancel_initiated_at": null,
+        "results_url": null
+    })
+}
+
+#[tokio::test]
+async fn batches_are_created_from_message_requests() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages/batches"))
+        .and(body_json(json!({"requests": [
+            {"custom_id": "first", "params": {
+                "model": "claude-3-haiku-20240307",
+                "messages": [{"role": "user", "content": "Hello"}],
+                "max_tokens": 16,
+                "stream": false
+            }},
+            {"custom_id": "second", "params": {
+                "model": "claude-3-haiku-20240307",
+                "messages": [{"role": "user", "content": "Bye"}],
+                "max_tokens": 16,
+                "stream": false
+            }}
+        ]})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(batch("msgbatch_01", "in_progress")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let batch = client(&server)
+        .create_message_batch(vec![
+            MessageBatchRequest::new("first", request("Hello")),
+            MessageBatchRequest::new("second", request("Bye")),
+        ])
+        .await
+        .unwrap();
+
+    assert_eq!(batch.id, "msgbatch_01");
+    assert_eq!(batch.processing_status, ProcessingStatus::InProgress);
+    assert_eq!(batch.request_counts, RequestCounts { processing: 1, succeeded: 1, ..Default::default() });
+}
+
+#[tokio::test]
+async fn batch_requests_send_their_betas_and_extra_body_fields() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages/batches"))
+        .and(header("anthropic-beta", "context-1m-2025-08-07"))
+        .and(body_partial_json(
+            json!({"requests": [{"custom_id": "first", "params": {"metadata": {"user_id": "user_01"}}}]}),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(batch("msgbatch_01", "in_progress")))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let mut extra_body = serde_json::Map::new();
+    extra_body.insert("metadata".to_string(), json!({"user_id": "user_01"}));
+    let params = CreateMessageRequest { betas: vec![Beta::Context1m], extra_body, ..request("Hello") };
+
+    let batch = client(&server).create_message_batch(vec![MessageBatchRequest::new("first", params)]).await.unwrap();
+    assert_eq!(batch.id, "msgbatch_01");
+
+    let mut extra_headers = HeaderMap::new();
+    extra_headers.insert("x-trace-id", HeaderValue::from_static("trace_01"));
+    let params = CreateMessageRequest { extra_headers, ..request("Hello") };
+    let result = client(&server).create_message_batch(vec![MessageBatchRequest::new("first", params)]).await;
+    assert!(matches!(result, Err(AnthropicError::InvalidArgument(_))));
+}
+
+#[tokio::test]
+async fn batches_send_their_idempotency_key() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages/batches"))
+        .and(header("idempotency-key", "key_01"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(batch("msgbatch_01", "in_progress")))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let batch_request = CreateMessageBatchRequest {
+        requests: vec![MessageBatchRequest::new("first", request("Hello"))],
+        idempotency_key: Some("key_01".into()),
+    };
+
+    let batch = client(&server).create_message_batch(batch_request).await.unwrap();
+    assert_eq!(batch.id, "msgbatch_01");
+
+    // The key of the batch is the only one sent.
+    let params = CreateMessageRequest { idempotency_key: Some("key_02".into()), ..request("Hello") };
+    let result = client(&server).create_message_batch(vec![MessageBatchRequest::new("first", params)]).await;
+    assert!(matches!(result, Err(AnthropicError::InvalidArgument(_))));
+}
+
+#[tokio::test]
+async fn batches_are_retrieved_listed_canceled_and_deleted() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/messages/batches/msgbatch_01"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(batch("msgbatch_01", "in_progress")))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/messages/batches"))
+        .and(query_param("after_id", "msgbatch_01"))
+        .and(query_param("limit", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "data": [batch("msgbatch_02", "ended")],
+            "has_more": false,
+            "first_id": "msgbatch_02",
+            "last_id": "msgbatch_02"
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages/batches/msgbatch_01/cancel"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(batch("msgbatch_01", "canceling")))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/v1/messages/batches/msgbatch_01"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({"id": "msgbatch_01", "type": "message_batch_deleted"})),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = client(&server);
+
+    assert_eq!(client.get_message_batch("msgbatch_01").await.unwrap().id, "msgbatch_01");
+    let params = ListParamsBuilder::default().after_id("msgbatch_01").limit(2u32).build().unwrap();
+    let page = client.list_message_batches(&params).await.unwrap();
+    assert_eq!(page.data[0].processing_status, ProcessingStatus::Ended);
+    assert!(!page.has_more);
+    assert_eq!(page.last_id.as_deref(), Some("msgbatch_02"));
+    assert_eq!(
+        client.cancel_message_batch("msgbatch_01").await.unwrap().processing_status,
+        ProcessingStatus::Canceling
+    );
+    assert_eq!(client.delete_message_batch("msgbatch_01").await.unwrap().id, "msgbatch_01");
+}
+
+#[tokio::test]
+async fn batch_results_are_streamed_from_jsonl() {
+    let server = MockServer::start().await;
+    let message = json!({
+        "id": "msg_01",
+        "type": "message",
+        "role": "assistant",
+        "content": [{"type": "text", "text": "Hi"}],
+        "model": "claude-3-haiku-20240307",
+        "stop_reason": "end_turn",
+        "stop_sequence": null,
+        "usage": {"input_tokens": 1, "output_tokens": 1}
+    });
+    let results = [
+        json!({"custom_id": "first", "result": {"type": "succeeded", "message": message}}),
+        json!({"custom_id": "second", "result": {"type": "errored", "error": {
+            "type": "error", "error": {"type": "invalid_request_error", "message": "max_tokens: too large"}
+        }}}),
+        json!({"custom_id": "third", "result": {"type": "canceled"}}),
+        json!({"custom_id": "fourth", "result": {"type": "expired"}}),
+    ]
+    .map(|result| result.to_string())
+    .join("\n");
+    Mock::given(method("GET"))
+        .and(path("/v1/messages/batches/msgbatch_01/results"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(results, "application/binary"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let results: Vec<MessageBatchIndividualResponse> =
+        client(&server).message_batch_results("msgbatch_01").await.unwrap().map(Result::unwrap).collect().await;
+
+    assert_eq!(results.len(), 4);
+    assert_eq!(results[0].custom_id, "first");
+    assert!(matches!(&results[0].result, MessageBatchResult::Succeeded { message } if message.id == "msg_01"));
+    assert!(matches!(
+        &results[1].result,
+        MessageBatchResult::Errored { error } if error.kind == ApiErrorKind::InvalidRequestError
+    ));
+    assert!(matches!(results[2].result, MessageBatchResult::Canceled));
+    assert!(matches!(results[3].result, MessageBatchResult::Expired));
+}
+
+#[tokio::test]
+async fn batch_results_are_joined_with_the_requests_by_custom_id() {
+    let server = MockServer::start().await;
+    let results = [
+        json!({"custom_id": "third", "result": {"type": "expired"}}),
+        json!({"custom_id": "first", "result": {"type": "canceled"}}),
+    ]
+    .map(|result| result.to_string())
+    .join("\n");
+    Mock::given(method("GET"))
+        .and(path("/v1/messages/batches/msgbatch_01/results"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(results, "application/binary"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let batch_request = CreateMessageBatchRequest::from(vec![
+        MessageBatchRequest::new("first", request("Hello")),
+        MessageBatchRequest::new("second", request("Hi")),
+        MessageBatchRequest::new("third", request("Hey")),
+    ]);
+
+    let results = client(&server).message_batch_results("msgbatch_01").await.unwrap();
+    let joined = batch_request.join_results(results).await.unwrap();
+
+    let custom_ids: Vec<&str> = joined.iter().map(|(request, _)| request.custom_id.as_str()).collect();
+    assert_eq!(custom_ids, ["first", "second", "third"]);
+    assert!(matches!(joined[0].1, Some(MessageBatchResult::Canceled)));
+    assert!(joined[1].1.is_none());
+    assert!(matches!(joined[2].1, Some(MessageBatchResult::Expired)));
+}
+
+#[tokio::test]
+async fn streaming_batch_requests_are_rejected() {
+    let server = MockServer::start().await;
+    let request = CreateMessageRequest { stream: true, ..request("Hello") };
+
+    let result = client(&server).create_message_batch(vec![MessageBatchRequest::new("first", request)]).await;
+
+    assert!(matches!(result, Err(AnthropicError::InvalidArgument(_))));
+}
+
+#[tokio::test]
+async fn wait_for_batch_polls_until_the_processing_ends() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/messages/batches/msgbatch_01"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(batch("msgbatch_01", "in_progress")))
+        .up_to_n_times(2)
+        .expect(2)
+        .mount(&server)
+        .await;
+    let mut ended = batch("msgbatch_01", "ended");
+    ended["request_counts"] = json!({"processing": 0, "succeeded": 2, "errored": 0, "canceled": 0, "expired": 0});
+    Mock::given(method("GET"))
+        .and(path("/v1/messages/batches/msgbatch_01"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(ended))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/messages/batches/msgbatch_01/results"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw(
+                "{\"custom_id\": \"first\", \"result\": {\"type\": \"canceled\"}}\n",
+                "application/binary",
+            ),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let options = WaitForBatchOptions::new().poll_interval(Duration::from_millis(5)).on_progress({
+        let progress = progress.clone();
+        move |batch| progress.lock().unwrap().push(batch.request_counts.succeeded)
+    });
+    let results: Vec<_> = client(&server).wait_for_batch("msgbatch_01", options).await.unwrap().collect().await;
+
+    assert_eq!(*progress.lock().unwrap(), vec![1, 1, 2]);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].as_ref().unwrap().custom_id, "first");
+}
+
+#[tokio::test]
+async fn wait_for_batch_times_out() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/messages/batches/msgbatch_01"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(batch("msgbatch_01", "in_progress")))
+        .mount(&server)
+        .await;
+
+    let options = WaitForBatchOptions::new().poll_interval(Duration::from_millis(5)).timeout(Duration::from_millis(20));
+    let result = client(&server).wait_for_batch("msgbatch_01", options).await;
+
+    assert!(matches!(result, Err(AnthropicError::BatchTimeout { batch_id, .. }) if batch_id == "msgbatch_01"));
+}
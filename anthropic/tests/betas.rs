@@ -0,0 +1,132 @@
+mod common;
+
+use anthropic::beta::Beta;
+use anthropic::client::ClientBuilder;
+use anthropic::error::AnthropicError;
+use anthropic::types::{CountTokensRequestBuilder, CreateMessageRequestBuilder, InputMessage};
+use common::message_response;
+use reqwest::header::{HeaderMap, HeaderValue};
+use serde_json::json;
+use wiremock::matchers::{body_json, header, headers, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn request_betas_headers_and_body_fields_are_merged_with_the_client_ones() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(headers("anthropic-beta", vec!["files-api-2025-04-14", "interleaved-thinking-2025-05-14", "my-beta"]))
+        .and(header("anthropic-version", "2024-01-01"))
+        .and(header("x-trace-id", "trace_01"))
+        .and(body_json(json!({
+            "model": "claude-3-haiku-20240307",
+            "messages": [{"role": "user", "content": "How many toes do dogs have?"}],
+            "max_tokens": 256,
+            "stream": false,
+            "thinking": {"type": "enabled", "budget_tokens": 1024}
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(message_response()))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = ClientBuilder::default()
+        .api_key("test-key".to_string())
+        .api_base(server.uri())
+        .beta(Beta::FilesApi)
+        .beta("interleaved-thinking-2025-05-14")
+        .build()
+        .unwrap();
+
+    let mut extra_headers = HeaderMap::new();
+    extra_headers.insert("anthropic-version", HeaderValue::from_static("2024-01-01"));
+    extra_headers.insert("x-trace-id", HeaderValue::from_static("trace_01"));
+    let mut extra_body = serde_json::Map::new();
+    extra_body.insert("thinking".to_string(), json!({"type": "enabled", "budget_tokens": 1024}));
+    let request = CreateMessageRequestBuilder::default()
+        .model("claude-3-haiku-20240307")
+        .messages(vec![InputMessage::user("How many toes do dogs have?")])
+        .max_tokens(256)
+        .betas(vec![Beta::InterleavedThinking, Beta::from("my-beta")])
+        .extra_headers(extra_headers)
+        .extra_body(extra_body)
+        .build()
+        .unwrap();
+    client.create_message(request).await.unwrap();
+
+    // The request betas are not kept by the client.
+    assert_eq!(client.betas, [Beta::FilesApi, Beta::InterleavedThinking]);
+}
+
+#[tokio::test]
+async fn count_tokens_requests_send_their_betas() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages/count_tokens"))
+        .and(header("anthropic-beta", "context-1m-2025-08-07"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"input_tokens": 8})))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = ClientBuilder::default().api_key("test-key".to_string()).api_base(server.uri()).build().unwrap();
+
+    let request = CountTokensRequestBuilder::default()
+        .model("claude-3-haiku-20240307")
+        .messages(vec![InputMessage::user("Hello")])
+        .betas(vec![Beta::Context1m])
+        .build()
+        .unwrap();
+    assert_eq!(client.count_tokens(request).await.unwrap().input_tokens, 8);
+}
+
+#[tokio::test]
+async fn beta_header_in_extra_headers_replaces_the_client_betas() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages/count_tokens"))
+        .and(header("anthropic-beta", "context-1m-2025-08-07"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"input_tokens": 8})))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = ClientBuilder::default()
+        .api_key("test-key".to_string())
+        .api_base(server.uri())
+        .beta(Beta::FilesApi)
+        .build()
+        .unwrap();
+
+    let mut extra_headers = HeaderMap::new();
+    extra_headers.insert("anthropic-beta", HeaderValue::from_static("context-1m-2025-08-07"));
+    let request = CountTokensRequestBuilder::default()
+        .model("claude-3-haiku-20240307")
+        .messages(vec![InputMessage::user("Hello")])
+        .extra_headers(extra_headers)
+        .build()
+        .unwrap();
+    assert_eq!(client.count_tokens(request).await.unwrap().input_tokens, 8);
+}
+
+#[tokio::test]
+async fn invalid_betas_are_rejected() {
+    let server = MockServer::start().await;
+    let client = ClientBuilder::default().api_key("test-key".to_string()).api_base(server.uri()).build().unwrap();
+
+    let request = CountTokensRequestBuilder::default()
+        .model("claude-3-haiku-20240307")
+        .messages(vec![InputMessage::user("Hello")])
+        .betas(vec![Beta::from("my-beta\n")])
+        .build()
+        .unwrap();
+    let result = client.count_tokens(request).await;
+
+    assert!(matches!(result, Err(AnthropicError::InvalidArgument(_))));
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
+
+#[test]
+fn beta_names_round_trip() {
+    assert_eq!(Beta::from("files-api-2025-04-14"), Beta::FilesApi);
+    assert_eq!(Beta::from("new-beta-2026-01-01"), Beta::Other("new-beta-2026-01-01".to_string()));
+    assert_eq!(serde_json::to_value(Beta::CodeExecution).unwrap(), json!("code-execution-2025-08-25"));
+    assert_eq!(Beta::McpClient.to_string(), "mcp-client-2025-04-04");
+}
@@ -0,0 +1,62 @@
+//! Fixtures shared by the integration tests.
+// Each test crate uses only some of the fixtures.
+#![allow(dead_code)]
+
+use std::time::Duration;
+
+use anthropic::client::{Client, ClientBuilder};
+use anthropic::types::{
+    CountTokensRequest, CountTokensRequestBuilder, CreateMessageRequest, CreateMessageRequestBuilder, InputMessage,
+};
+use backoff::ExponentialBackoffBuilder;
+use serde_json::json;
+use wiremock::MockServer;
+
+/// A client of `api_base` retrying the failed requests without waiting.
+pub fn client_builder(api_base: &str) -> ClientBuilder {
+    let backoff = ExponentialBackoffBuilder::new()
+        .with_initial_interval(Duration::from_millis(1))
+        .with_max_interval(Duration::from_millis(5))
+        .build();
+    let mut builder = ClientBuilder::default();
+    builder.api_key("test-key".to_string()).api_base(api_base.to_string()).backoff(backoff);
+    builder
+}
+
+/// A client of the mock server.
+pub fn client(server: &MockServer) -> Client {
+    client_builder(&server.uri()).build().unwrap()
+}
+
+/// A message request asking how many toes dogs have.
+pub fn request() -> CreateMessageRequest {
+    CreateMessageRequestBuilder::default()
+        .model("claude-3-haiku-20240307")
+        .messages(vec![InputMessage::user("How many toes do dogs have?")])
+        .max_tokens(256)
+        .build()
+        .unwrap()
+}
+
+/// A token counting request.
+pub fn count_tokens_request() -> CountTokensRequest {
+    CountTokensRequestBuilder::default()
+        .model("claude-3-haiku-20240307")
+        .messages(vec![InputMessage::user("Hello")])
+        .build()
+        .unwrap()
+}
+
+/// The body of the response to [request].
+pub fn message_response() -> serde_json::Value {
+    json!({
+        "id": "msg_01",
+        "type": "message",
+        "role": "assistant",
+        "content": [{"type": "text", "text": "Dogs have 18 toes."}],
+        "model": "claude-3-haiku-20240307",
+        "stop_reason": "end_turn",
+        "stop_sequence": null,
+        "usage": {"input_tokens": 12, "output_tokens": 8}
+    })
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use anthropic::types::{CountTokensRequestBuilder, CreateMessageRequestBuilder, InputMessage, ToolBuilder};
+use common::client;
+use serde_json::json;
+use wiremock::matchers::{body_json, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn count_tokens_posts_to_count_tokens_endpoint() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages/count_tokens"))
+        .and(header("x-api-key", "test-key"))
+        .and(body_json(json!({
+            "model": "claude-3-haiku-20240307",
+            "messages": [{"role": "user", "content": "How many toes do dogs have?"}],
+            "system": "Be brief.",
+            "tools": [{"name": "get_weather", "input_schema": {"type": "object"}}]
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"input_tokens": 42})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let request = CountTokensRequestBuilder::default()
+        .model("claude-3-haiku-20240307")
+        .messages(vec![InputMessage::user("How many toes do dogs have?")])
+        .system("Be brief.")
+        .tools(vec![
+            ToolBuilder::default().name("get_weather").input_schema(json!({"type": "object"})).build().unwrap(),
+        ])
+        .build()
+        .unwrap();
+    let response = client(&server).count_tokens(request).await.unwrap();
+
+    assert_eq!(response.input_tokens, 42);
+}
+
+#[tokio::test]
+async fn message_requests_are_counted_without_generation_parameters() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages/count_tokens"))
+        .and(body_json(json!({
+            "model": "claude-3-haiku-20240307",
+            "messages": [{"role": "user", "content": "Hello"}]
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"input_tokens": 8})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let request = CreateMessageRequestBuilder::default()
+        .model("claude-3-haiku-20240307")
+        .messages(vec![InputMessage::user("Hello")])
+        .max_tokens(256)
+        .temperature(0.5)
+        .build()
+        .unwrap();
+
+    assert_eq!(client(&server).count_tokens(request).await.unwrap().input_tokens, 8);
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use anthropic::stream::MessageAccumulator;
+use anthropic::types::{
+    Citation, ContentBlock, CreateMessageRequestBuilder, CreateMessageResponse, DocumentSource, InputMessage,
+};
+use common::client;
+use serde_json::json;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn grass_citation() -> Citation {
+    Citation::CharLocation {
+        cited_text: "The grass is green.".to_string(),
+        document_index: 0,
+        document_title: Some("Facts".to_string()),
+        start_char_index: 0,
+        end_char_index: 20,
+    }
+}
+
+#[test]
+fn document_blocks_are_serialized() {
+    let message = InputMessage::user(vec![
+        ContentBlock::cited_document(DocumentSource::text("The grass is green."), "Facts"),
+        ContentBlock::Document {
+            source: DocumentSource::pdf(b"%PDF-1.7").unwrap(),
+            title: None,
+            context: Some("Attached by the user".to_string()),
+            citations: None,
+        },
+        ContentBlock::document(DocumentSource::content(vec![ContentBlock::text("First chunk.")])),
+        ContentBlock::text("What color is the grass?"),
+    ]);
+
+    assert_eq!(
+        serde_json::to_value(message).unwrap(),
+        json!({"role": "user", "content": [
+            {
+                "type": "document",
+                "source": {"type": "text", "media_type": "text/plain", "data": "The grass is green."},
+                "title": "Facts",
+                "citations": {"enabled": true}
+            },
+            {
+                "type": "document",
+                "source": {"type": "base64", "media_type": "application/pdf", "data": "JVBERi0xLjc="},
+                "context": "Attached by the user"
+            },
+            {
+                "type": "document",
+                "source": {"type": "content", "content": [{"type": "text", "text": "First chunk."}]}
+            },
+            {"type": "text", "text": "What color is the grass?"}
+        ]})
+    );
+}
+
+#[test]
+fn citations_are_deserialized_from_responses() {
+    let response: CreateMessageResponse = serde_json::from_value(json!({
+        "id": "msg_03",
+        "type": "message",
+        "role": "assistant",
+        "content": [
+            {"type": "text", "text": "According to the document, "},
+            {"type": "text", "text": "the grass is green", "citations": [{
+                "type": "char_location",
+                "cited_text": "The grass is green.",
+                "document_index": 0,
+                "document_title": "Facts",
+                "start_char_index": 0,
+                "end_char_index": 20
+            }, {
+                "type": "page_location",
+                "cited_text": "Grass is usually green.",
+                "document_index": 1,
+                "document_title": null,
+                "start_page_number": 2,
+                "end_page_number": 3
+            }]}
+        ],
+        "model": "claude-3-5-sonnet-20241022",
+        "stop_reason": "end_turn",
+        "stop_sequence": null,
+        "usage": {"input_tokens": 610, "output_tokens": 12}
+    }))
+    .unwrap();
+
+    assert_eq!(
+        response.content[1],
+        ContentBlock::Text {
+            text: "the grass is green".to_string(),
+            citations: Some(vec![
+                grass_citation(),
+                Citation::PageLocation {
+                    cited_text: "Grass is usually green.".to_string(),
+                    document_index: 1,
+                    document_title: None,
+                    start_page_number: 2,
+                    end_page_number: 3,
+                }
+            ]),
+        }
+    );
+}
+
+#[tokio::test]
+async fn streamed_citations_are_accumulated() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw(include_str!("fixtures/citations_stream.sse"), "text/event-stream"),
+        )
+        .mount(&server)
+        .await;
+    let request = CreateMessageRequestBuilder::default()
+        .model("claude-3-5-sonnet-20241022")
+        .messages(vec![InputMessage::user(vec![
+            ContentBlock::cited_document(DocumentSource::text("The grass is green."), "Facts"),
+            ContentBlock::text("What color is the grass?"),
+        ])])
+        .max_tokens(256)
+        .stream(true)
+        .build()
+        .unwrap();
+
+    let stream = client(&server).create_message_stream(request).await.unwrap();
+    let message = MessageAccumulator::collect(stream).await.unwrap();
+
+    assert_eq!(
+        message.content,
+        vec![ContentBlock::Text { text: "The grass is green.".to_string(), citations: Some(vec![grass_citation()]) }]
+    );
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use std::time::Duration;
+
+use anthropic::beta::Beta;
+use anthropic::client::ClientBuilder;
+use anthropic::pagination::ListParams;
+use anthropic::types::{DocumentSource, ImageSource};
+use backoff::ExponentialBackoffBuilder;
+use common::client;
+use serde_json::json;
+use tokio_stream::StreamExt;
+use wiremock::matchers::{body_string_contains, header, header_regex, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn file(id: &str, filename: &str, mime_type: &str) -> serde_json::Value {
+    json!({
+        "type": "file",
+        "id": id,
+        "filename": filename,
+        "mime_type": mime_type,
+        "size_bytes": 11,
+        "created_at": "2025-04-14T00:00:00Z"
+    })
+}
+
+#[tokio::test]
+async fn files_are_uploaded_as_multipart_forms() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/files"))
+        .and(header("anthropic-beta", Beta::FilesApi.as_str()))
+        .and(header_regex("content-type", "^multipart/form-data; boundary="))
+        .and(body_string_contains("filename=\"notes.txt\""))
+        .and(body_string_contains("Content-Type: text/plain"))
+        .and(body_string_contains("hello world"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(file("file_1", "notes.txt", "text/plain")))
+        .expect(3)
+        .mount(&server)
+        .await;
+    let client = client(&server);
+
+    let uploaded = client.upload_file_bytes("notes.txt", "text/plain", b"hello world".to_vec()).await.unwrap();
+    assert_eq!(uploaded.id, "file_1");
+    assert_eq!(uploaded.size_bytes, 11);
+    assert!(!uploaded.downloadable);
+
+    let reader = std::io::Cursor::new(b"hello world".to_vec());
+    client.upload_file_reader("notes.txt", "text/plain", reader).await.unwrap();
+
+    let file_path = std::env::temp_dir().join(format!("anthropic-files-{}", std::process::id())).join("notes.txt");
+    std::fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+    std::fs::write(&file_path, "hello world").unwrap();
+    client.upload_file(&file_path).await.unwrap();
+    std::fs::remove_dir_all(file_path.parent().unwrap()).unwrap();
+}
+
+#[tokio::test]
+async fn uploads_from_memory_are_retried() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/files"))
+        .respond_with(ResponseTemplate::new(529).set_body_json(json!({
+            "type": "error",
+            "error": {"type": "overloaded_error", "message": "Overloaded"}
+        })))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/files"))
+        .and(body_string_contains("hello world"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(file("file_1", "notes.txt", "text/plain")))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let backoff = ExponentialBackoffBuilder::new()
+        .with_initial_interval(Duration::from_millis(1))
+        .with_max_interval(Duration::from_millis(5))
+        .build();
+    let client = ClientBuilder::default()
+        .api_key("test-key".to_string())
+        .api_base(server.uri())
+        .backoff(backoff)
+        .build()
+        .unwrap();
+
+    let uploaded = client.upload_file_bytes("notes.txt", "text/plain", b"hello world".to_vec()).await.unwrap();
+    assert_eq!(uploaded.id, "file_1");
+}
+
+#[tokio::test]
+async fn files_are_listed_retrieved_downloaded_and_deleted() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/files"))
+        .and(header("anthropic-beta", Beta::FilesApi.as_str()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "data": [file("file_2", "chart.png", "image/png"), file("file_1", "notes.txt", "text/plain")],
+            "has_more": false,
+            "first_id": "file_2",
+            "last_id": "file_1"
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/files/file_1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(file("file_1", "notes.txt", "text/plain")))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/files/file_1/content"))
+        .and(header("anthropic-beta", Beta::FilesApi.as_str()))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(b"hello world".to_vec(), "text/plain"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/v1/files/file_1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"type": "file_deleted", "id": "file_1"})))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = client(&server);
+
+    let ids: Vec<String> = client.list_all_files(ListParams::default()).map(|file| file.unwrap().id).collect().await;
+    assert_eq!(ids, ["file_2", "file_1"]);
+
+    assert_eq!(client.get_file("file_1").await.unwrap().filename, "notes.txt");
+    assert_eq!(client.download_file("file_1").await.unwrap(), b"hello world");
+    assert_eq!(client.delete_file("file_1").await.unwrap().id, "file_1");
+}
+
+#[test]
+fn file_sources_serialize_with_their_id() {
+    assert_eq!(
+        serde_json::to_value(ImageSource::from_file_id("file_1")).unwrap(),
+        json!({"type": "file", "file_id": "file_1"})
+    );
+    assert_eq!(
+        serde_json::from_value::<DocumentSource>(json!({"type": "file", "file_id": "file_2"})).unwrap(),
+        DocumentSource::from_file_id("file_2")
+    );
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use std::time::Duration;
+
+use anthropic::client::{Client, ClientBuilder};
+use anthropic::config::AnthropicConfig;
+use anthropic::error::AnthropicError;
+use anthropic::options::RequestOptions;
+use common::count_tokens_request;
+use reqwest::header::{HeaderMap, HeaderValue};
+use serde_json::json;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn config(api_base: String) -> AnthropicConfig {
+    AnthropicConfig {
+        api_key: "test-key".to_string(),
+        api_base: Some(api_base),
+        default_model: None,
+        timeout_secs: None,
+        connect_timeout_secs: None,
+        proxy: None,
+    }
+}
+
+#[tokio::test]
+async fn custom_http_client_is_used() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages/count_tokens"))
+        .and(header("x-api-key", "test-key"))
+        .and(header("x-proxy-authorization", "team-a"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"input_tokens": 8})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut default_headers = HeaderMap::new();
+    default_headers.insert("x-proxy-authorization", HeaderValue::from_static("team-a"));
+    let http_client = reqwest::Client::builder().default_headers(default_headers).build().unwrap();
+    let client = ClientBuilder::default()
+        .api_key("test-key".to_string())
+        .api_base(server.uri())
+        .http_client(http_client)
+        .build()
+        .unwrap();
+
+    assert_eq!(client.count_tokens(count_tokens_request()).await.unwrap().input_tokens, 8);
+}
+
+#[tokio::test]
+async fn config_proxy_forwards_the_requests() {
+    let proxy = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages/count_tokens"))
+        .and(header("host", "api.anthropic.test"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"input_tokens": 8})))
+        .expect(1)
+        .mount(&proxy)
+        .await;
+
+    let config = AnthropicConfig { proxy: Some(proxy.uri()), ..config("http://api.anthropic.test".to_string()) };
+    let client = Client::try_from(config).unwrap();
+
+    assert_eq!(client.count_tokens(count_tokens_request()).await.unwrap().input_tokens, 8);
+}
+
+#[tokio::test]
+async fn config_timeout_fails_slow_requests() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages/count_tokens"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({"input_tokens": 8})).set_delay(Duration::from_secs(5)),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let config = AnthropicConfig { timeout_secs: Some(1), ..config(server.uri()) };
+    let client = Client::try_from(config).unwrap().with_options(RequestOptions::new().max_retries(0));
+
+    let result = client.count_tokens(count_tokens_request()).await;
+    assert!(matches!(result, Err(AnthropicError::Reqwest(e)) if e.is_timeout()));
+}
+
+#[test]
+fn invalid_config_proxy_is_rejected() {
+    let config = AnthropicConfig { proxy: Some("http://[::1".to_string()), ..config(String::new()) };
+
+    assert!(matches!(Client::try_from(config), Err(AnthropicError::Reqwest(_))));
+}
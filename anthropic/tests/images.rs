@@ -0,0 +1,60 @@
+use anthropic::error::AnthropicError;
+use anthropic::types::{ContentBlock, ImageMediaType, ImageSource, InputMessage, MAX_IMAGE_SIZE};
+use serde_json::json;
+
+const PNG: &[u8] = &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 0x0D];
+
+#[test]
+fn media_type_is_sniffed_from_magic_bytes() {
+    assert_eq!(ImageMediaType::sniff(&[0xFF, 0xD8, 0xFF, 0xE0]), Some(ImageMediaType::Jpeg));
+    assert_eq!(ImageMediaType::sniff(PNG), Some(ImageMediaType::Png));
+    assert_eq!(ImageMediaType::sniff(b"GIF89a\x01\x00"), Some(ImageMediaType::Gif));
+    assert_eq!(ImageMediaType::sniff(b"RIFF\x24\x00\x00\x00WEBPVP8 "), Some(ImageMediaType::Webp));
+    assert_eq!(ImageMediaType::sniff(b"%PDF-1.7"), None);
+}
+
+#[test]
+fn image_blocks_are_built_from_bytes_and_files() {
+    let path = std::env::temp_dir().join(format!("anthropic-rs-image-{}.png", std::process::id()));
+    std::fs::write(&path, PNG).unwrap();
+    let source = ImageSource::from_path(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(source, ImageSource::from_bytes(PNG).unwrap());
+    let message = InputMessage::user(vec![source.into(), ContentBlock::text("What is this?")]);
+    assert_eq!(
+        serde_json::to_value(message).unwrap(),
+        json!({"role": "user", "content": [
+            {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": "iVBORw0KGgoAAAAN"}},
+            {"type": "text", "text": "What is this?"}
+        ]})
+    );
+}
+
+#[test]
+fn url_image_sources_are_serialized() {
+    let block = ContentBlock::image(ImageSource::from_url("https://example.com/dog.jpg"));
+
+    assert_eq!(
+        serde_json::to_value(block).unwrap(),
+        json!({"type": "image", "source": {"type": "url", "url": "https://example.com/dog.jpg"}})
+    );
+}
+
+#[test]
+fn unsupported_or_oversized_images_are_rejected() {
+    assert!(matches!(ImageSource::from_bytes(b"not an image"), Err(AnthropicError::InvalidArgument(_))));
+
+    // The limit applies to the base64 encoded data, 4 bytes for every 3 bytes of the image.
+    let mut largest = PNG.to_vec();
+    largest.resize(MAX_IMAGE_SIZE / 4 * 3, 0);
+    match ImageSource::from_bytes(&largest).unwrap() {
+        ImageSource::Base64 { data, .. } => assert_eq!(data.len(), MAX_IMAGE_SIZE),
+        source => panic!("unexpected source {source:?}"),
+    }
+    let mut oversized = largest;
+    oversized.push(0);
+    assert!(matches!(ImageSource::from_bytes(&oversized), Err(AnthropicError::InvalidArgument(_))));
+
+    assert!(matches!(ImageSource::from_path("/nonexistent/dog.png"), Err(AnthropicError::Io(_))));
+}
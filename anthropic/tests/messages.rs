@@ -0,0 +1,285 @@
+mod common;
+
+use std::time::Duration;
+
+use anthropic::client::ClientBuilder;
+use anthropic::error::{AnthropicError, ApiErrorKind, MAX_BODY_SNIPPET_CHARS};
+use anthropic::types::{
+    CompleteRequestBuilder, ContentBlock, CreateMessageRequest, CreateMessageRequestBuilder, CreateMessageResponse,
+    ImageSource, InputMessage, Role, StopReason, UnknownType,
+};
+use anthropic::{DEFAULT_COMPLETION_MODEL, DEFAULT_MODEL};
+use common::{client, message_response, request};
+use reqwest::StatusCode;
+use serde_json::json;
+use wiremock::matchers::{body_json, body_partial_json, header, method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+#[tokio::test]
+async fn create_message_posts_to_messages_endpoint() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(header("x-api-key", "test-key"))
+        .and(header("anthropic-version", "2023-06-01"))
+        .and(body_json(json!({
+            "model": "claude-3-haiku-20240307",
+            "messages": [
+                {"role": "user", "content": "How many toes do dogs have?"},
+                {"role": "assistant", "content": [{"type": "text", "text": "Dogs have"}]}
+            ],
+            "system": "Be brief.",
+            "max_tokens": 256,
+            "stream": false
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(message_response()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let request = CreateMessageRequestBuilder::default()
+        .model("claude-3-haiku-20240307")
+        .messages(vec![
+            InputMessage::user("How many toes do dogs have?"),
+            InputMessage::assistant(vec![ContentBlock::text("Dogs have")]),
+        ])
+        .system("Be brief.")
+        .max_tokens(256)
+        .build()
+        .unwrap();
+
+    let response = client(&server).create_message(request).await.unwrap();
+
+    assert_eq!(response.id, "msg_01");
+    assert_eq!(response.role, Role::Assistant);
+    assert_eq!(response.stop_reason, Some(StopReason::EndTurn));
+    assert_eq!(response.content[0].as_text(), Some("Dogs have 18 toes."));
+    assert_eq!(response.usage.input_tokens, 12);
+    assert_eq!(response.usage.output_tokens, 8);
+}
+
+#[tokio::test]
+async fn create_message_with_response_exposes_http_metadata() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("request-id", "req_01")
+                .insert_header("anthropic-ratelimit-requests-remaining", "49")
+                .set_body_json(message_response()),
+        )
+        .mount(&server)
+        .await;
+
+    let request = CreateMessageRequestBuilder::default()
+        .model("claude-3-haiku-20240307")
+        .messages(vec![InputMessage::user("How many toes do dogs have?")])
+        .max_tokens(256)
+        .build()
+        .unwrap();
+    let response = client(&server).create_message_with_response(request).await.unwrap();
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.request_id.as_deref(), Some("req_01"));
+    assert_eq!(response.headers["request-id"], "req_01");
+    assert_eq!(response.rate_limit.requests.remaining, Some(49));
+    assert!(response.elapsed > Duration::ZERO);
+    assert_eq!(response.id, "msg_01");
+    assert_eq!(response.into_data().usage.output_tokens, 8);
+}
+
+#[tokio::test]
+async fn create_message_surfaces_api_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(ResponseTemplate::new(400).insert_header("request-id", "req_01").set_body_json(json!({
+            "type": "error",
+            "error": {"type": "invalid_request_error", "message": "max_tokens: field required"}
+        })))
+        .mount(&server)
+        .await;
+
+    let request = CreateMessageRequestBuilder::default()
+        .model("claude-3-haiku-20240307")
+        .messages(vec![InputMessage::user("Hello")])
+        .build()
+        .unwrap();
+
+    match client(&server).create_message(request).await {
+        Err(AnthropicError::ApiError(error)) => {
+            assert_eq!(error.kind, ApiErrorKind::InvalidRequestError);
+            assert_eq!(error.message, "max_tokens: field required");
+            assert_eq!(error.status, Some(StatusCode::BAD_REQUEST));
+            assert_eq!(error.request_id.as_deref(), Some("req_01"));
+            assert_eq!(error.headers["request-id"], "req_01");
+            assert!(!error.is_retryable());
+            assert_eq!(error.to_string(), "invalid_request_error: max_tokens: field required (request id: req_01)");
+        }
+        other => panic!("expected an API error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn non_json_error_bodies_are_kept() {
+    let server = MockServer::start().await;
+    let page = format!("<html><body>{}</body></html>", "Forbidden by proxy. ".repeat(100));
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(ResponseTemplate::new(403).set_body_raw(page, "text/html"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let request = CreateMessageRequestBuilder::default()
+        .model("claude-3-haiku-20240307")
+        .messages(vec![InputMessage::user("Hello")])
+        .build()
+        .unwrap();
+
+    match client(&server).create_message(request).await {
+        Err(AnthropicError::UnexpectedResponse { status, body }) => {
+            assert_eq!(status, StatusCode::FORBIDDEN);
+            assert!(body.starts_with("<html><body>Forbidden by proxy."));
+            assert_eq!(body.chars().count(), MAX_BODY_SNIPPET_CHARS + 3);
+        }
+        other => panic!("expected an unexpected response error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn create_message_rejects_streaming_requests() {
+    let server = MockServer::start().await;
+    let request = CreateMessageRequestBuilder::default()
+        .model("claude-3-haiku-20240307")
+        .messages(vec![InputMessage::user("Hello")])
+        .max_tokens(16)
+        .stream(true)
+        .build()
+        .unwrap();
+
+    let result = client(&server).create_message(request).await;
+
+    assert!(matches!(result, Err(AnthropicError::InvalidArgument(_))));
+}
+
+#[test]
+fn unknown_types_are_preserved() {
+    let json = json!({
+        "id": "msg_01",
+        "type": "message",
+        "role": "assistant",
+        "content": [
+            {"type": "thinking", "thinking": "Dogs have paws.", "signature": "c2ln"},
+            {"type": "server_tool_use", "id": "srvtoolu_01", "name": "web_search", "input": {"query": "dog toes"}},
+            {"type": "text", "text": "Dogs have 18 toes."}
+        ],
+        "model": "claude-3-haiku-20240307",
+        "stop_reason": "model_context_window_exceeded",
+        "stop_sequence": null,
+        "usage": {"input_tokens": 12, "output_tokens": 8}
+    });
+
+    let response: CreateMessageResponse = serde_json::from_value(json.clone()).unwrap();
+
+    assert_eq!(
+        response.content[0],
+        ContentBlock::Thinking { thinking: "Dogs have paws.".to_string(), signature: "c2ln".to_string() }
+    );
+    assert_eq!(
+        response.content[1],
+        ContentBlock::Unknown(UnknownType { kind: "server_tool_use".to_string(), raw: json["content"][1].clone() })
+    );
+    assert_eq!(response.stop_reason, Some(StopReason::Unknown("model_context_window_exceeded".to_string())));
+    assert_eq!(serde_json::to_value(&response).unwrap(), json);
+}
+
+#[test]
+fn malformed_known_types_are_errors() {
+    let tool_use = json!({"type": "tool_use", "name": "get_weather", "input": {}});
+    assert!(serde_json::from_value::<ContentBlock>(tool_use).is_err());
+
+    assert!(serde_json::from_value::<ImageSource>(json!({"type": "url"})).is_err());
+    assert!(serde_json::from_value::<ContentBlock>(json!({"text": "no type"})).is_err());
+}
+
+#[tokio::test]
+async fn requests_without_model_use_the_default_model_of_the_client() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(body_partial_json(json!({"model": "claude-opus-4-1-20250805"})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(message_response()))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages/count_tokens"))
+        .and(body_partial_json(json!({"model": DEFAULT_MODEL})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"input_tokens": 12})))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let request = CreateMessageRequestBuilder::default()
+        .messages(vec![InputMessage::user("How many toes do dogs have?")])
+        .max_tokens(256)
+        .build()
+        .unwrap();
+
+    let opus_client = ClientBuilder::default()
+        .api_key("test-key".to_string())
+        .api_base(server.uri())
+        .default_model("claude-opus-4-1-20250805".to_string())
+        .build()
+        .unwrap();
+    opus_client.create_message(request.clone()).await.unwrap();
+    assert_eq!(client(&server).count_tokens(request).await.unwrap().input_tokens, 12);
+
+    // The Text Completions API only serves the legacy models.
+    let completion = CompleteRequestBuilder::default().prompt("Hello").build().unwrap();
+    assert_eq!(completion.model, DEFAULT_COMPLETION_MODEL);
+}
+
+#[tokio::test]
+async fn idempotency_key_is_sent_with_its_request_and_retries() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(header("idempotency-key", "key_01"))
+        .respond_with(ResponseTemplate::new(529))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(header("idempotency-key", "key_01"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(message_response()))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(|request: &Request| !request.headers.contains_key("idempotency-key"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(message_response()))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = client(&server);
+
+    // The retry of the request keeps its key, the other requests of the client do not have it.
+    let with_key = CreateMessageRequest { idempotency_key: Some("key_01".into()), ..request() };
+    client.create_message(with_key).await.unwrap();
+    client.create_message(request()).await.unwrap();
+}
+
+#[tokio::test]
+async fn invalid_idempotency_key_is_rejected() {
+    let server = MockServer::start().await;
+    let request = CreateMessageRequest { idempotency_key: Some("key\n01".into()), ..request() };
+
+    let result = client(&server).create_message(request).await;
+
+    assert!(matches!(result, Err(AnthropicError::InvalidArgument(_))));
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use anthropic::models::Model;
+use anthropic::pagination::ListParamsBuilder;
+use anthropic::types::CreateMessageRequestBuilder;
+use common::client;
+use serde_json::json;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn models_are_listed_and_retrieved() {
+    let server = MockServer::start().await;
+    let model = json!({
+        "type": "model",
+        "id": "claude-3-haiku-20240307",
+        "display_name": "Claude Haiku 3",
+        "created_at": "2024-03-07T00:00:00Z"
+    });
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .and(query_param("limit", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "data": [model],
+            "has_more": true,
+            "first_id": "claude-3-haiku-20240307",
+            "last_id": "claude-3-haiku-20240307"
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models/claude-3-haiku-20240307"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(model))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = client(&server);
+
+    let page = client.list_models(&ListParamsBuilder::default().limit(1u32).build().unwrap()).await.unwrap();
+    assert!(page.has_more);
+    assert_eq!(page.data[0].display_name, "Claude Haiku 3");
+    assert_eq!(page.data[0].model(), Model::Claude3Haiku);
+
+    let model = client.get_model("claude-3-haiku-20240307").await.unwrap();
+    assert_eq!(model.created_at.to_rfc3339(), "2024-03-07T00:00:00+00:00");
+}
+
+#[test]
+fn model_ids_round_trip() {
+    assert_eq!(Model::from("claude-3-haiku-20240307"), Model::Claude3Haiku);
+    assert_eq!(Model::from("claude-sonnet-4-5"), Model::Other("claude-sonnet-4-5".to_string()));
+    assert_eq!(String::from(Model::ClaudeSonnet4_5), "claude-sonnet-4-5-20250929");
+    assert_eq!(serde_json::to_value(Model::ClaudeOpus4_1).unwrap(), json!("claude-opus-4-1-20250805"));
+    assert_eq!(serde_json::from_value::<Model>(json!("claude-custom")).unwrap(), Model::Other("claude-custom".into()));
+
+    let request = CreateMessageRequestBuilder::default().model(Model::Claude3_5Haiku).max_tokens(16).build().unwrap();
+    assert_eq!(request.model, "claude-3-5-haiku-20241022");
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use std::time::Duration;
+
+use anthropic::error::AnthropicError;
+use anthropic::options::RequestOptions;
+use common::{client, count_tokens_request};
+use reqwest::header::HeaderValue;
+use serde_json::json;
+use wiremock::matchers::{header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn options_add_headers_and_query_params() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages/count_tokens"))
+        .and(header("x-trace-id", "trace_01"))
+        .and(query_param("trace", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"input_tokens": 8})))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages/count_tokens"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"input_tokens": 9})))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = client(&server);
+
+    let options = RequestOptions::new().header("x-trace-id", HeaderValue::from_static("trace_01")).query("trace", "1");
+    assert_eq!(client.with_options(options).count_tokens(count_tokens_request()).await.unwrap().input_tokens, 8);
+    // The options are not applied to the original client.
+    assert_eq!(client.count_tokens(count_tokens_request()).await.unwrap().input_tokens, 9);
+}
+
+#[tokio::test]
+async fn max_retries_overrides_the_retry_policy() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages/count_tokens"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let result =
+        client(&server).with_options(RequestOptions::new().max_retries(1)).count_tokens(count_tokens_request()).await;
+    assert!(matches!(result, Err(AnthropicError::UnexpectedResponse { .. })));
+}
+
+#[tokio::test]
+async fn timeout_fails_slow_requests() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages/count_tokens"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({"input_tokens": 8})).set_delay(Duration::from_secs(5)),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let options = RequestOptions::new().timeout(Duration::from_millis(50)).max_retries(0);
+    let result = client(&server).with_options(options).count_tokens(count_tokens_request()).await;
+    assert!(matches!(result, Err(AnthropicError::Reqwest(e)) if e.is_timeout()));
+}
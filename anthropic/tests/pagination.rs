@@ -0,0 +1,105 @@
+mod common;
+
+use anthropic::error::AnthropicError;
+use anthropic::pagination::{ListParams, ListParamsBuilder, Page};
+use common::client;
+use serde_json::{Value, json};
+use tokio_stream::StreamExt;
+use wiremock::matchers::{method, path, query_param, query_param_is_missing};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn page(ids: &[&str], has_more: bool) -> ResponseTemplate {
+    let data: Vec<Value> = ids
+        .iter()
+        .map(|id| json!({"type": "model", "id": id, "display_name": id, "created_at": "2024-03-07T00:00:00Z"}))
+        .collect();
+    ResponseTemplate::new(200).set_body_json(json!({
+        "data": data,
+        "has_more": has_more,
+        "first_id": ids.first(),
+        "last_id": ids.last()
+    }))
+}
+
+#[tokio::test]
+async fn all_pages_are_streamed_after_the_last_id() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .and(query_param_is_missing("after_id"))
+        .and(query_param("limit", "2"))
+        .respond_with(page(&["a", "b"], true))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .and(query_param("after_id", "b"))
+        .and(query_param("limit", "2"))
+        .respond_with(page(&["c"], false))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let params = ListParamsBuilder::default().limit(2u32).build().unwrap();
+    let ids: Vec<String> = client(&server).list_all_models(params).map(|model| model.unwrap().id).collect().await;
+
+    assert_eq!(ids, ["a", "b", "c"]);
+}
+
+#[tokio::test]
+async fn pagination_stops_at_the_first_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/messages/batches"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+            "type": "error",
+            "error": {"type": "authentication_error", "message": "invalid x-api-key"}
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let items: Vec<_> = client(&server).list_all_message_batches(ListParams::default()).collect().await;
+
+    assert_eq!(items.len(), 1);
+    assert!(matches!(items[0], Err(AnthropicError::ApiError(_))));
+}
+
+#[test]
+fn next_params_follow_the_listing_direction() {
+    let page = Page { data: vec![1, 2], has_more: true, first_id: Some("a".into()), last_id: Some("b".into()) };
+
+    let forward = ListParamsBuilder::default().after_id("z").limit(2u32).build().unwrap();
+    assert_eq!(page.next_params(&forward), Some(ListParams { after_id: Some("b".into()), ..forward }));
+
+    let backward = ListParamsBuilder::default().before_id("z").build().unwrap();
+    assert_eq!(page.next_params(&backward), Some(ListParams { before_id: Some("a".into()), ..backward.clone() }));
+
+    assert_eq!(Page { has_more: false, ..page }.next_params(&backward), None);
+}
+
+#[test]
+fn pages_are_fetched_as_the_stream_is_consumed() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let server = runtime.block_on(async {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .respond_with(page(&["a", "b"], true))
+            .expect(1)
+            .mount(&server)
+            .await;
+        server
+    });
+
+    // The stream is created outside of a runtime, and sends no request until it is polled.
+    let mut models = client(&server).list_all_models(ListParams::default());
+
+    runtime.block_on(async move {
+        assert!(server.received_requests().await.unwrap().is_empty());
+        assert_eq!(models.next().await.unwrap().unwrap().id, "a");
+        assert_eq!(models.next().await.unwrap().unwrap().id, "b");
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    });
+}
@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use anthropic::client::ClientBuilder;
+use anthropic::rate_limit::{RateLimit, RateLimitInfo, RateLimiter};
+use anthropic::types::{CreateMessageRequestBuilder, InputMessage};
+use chrono::{DateTime, SecondsFormat, Utc};
+use reqwest::header::{HeaderMap, HeaderValue};
+use serde_json::json;
+use tokio::time::Instant;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn rfc3339(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn exhausted_requests(reset: DateTime<Utc>) -> RateLimitInfo {
+    RateLimitInfo {
+        requests: RateLimit { limit: Some(50), remaining: Some(0), reset: Some(reset) },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn rate_limits_are_parsed_from_headers() {
+    let mut headers = HeaderMap::new();
+    headers.insert("anthropic-ratelimit-requests-limit", HeaderValue::from_static("50"));
+    headers.insert("anthropic-ratelimit-requests-remaining", HeaderValue::from_static("49"));
+    headers.insert("anthropic-ratelimit-requests-reset", HeaderValue::from_static("2024-05-01T12:00:01Z"));
+    headers.insert("anthropic-ratelimit-tokens-remaining", HeaderValue::from_static("0"));
+    headers.insert("anthropic-ratelimit-tokens-reset", HeaderValue::from_static("2000-01-01T00:00:00Z"));
+
+    let info = RateLimitInfo::from_headers(&headers);
+
+    assert_eq!(
+        info.requests,
+        RateLimit {
+            limit: Some(50),
+            remaining: Some(49),
+            reset: Some(DateTime::parse_from_rfc3339("2024-05-01T12:00:01Z").unwrap().into())
+        }
+    );
+    assert_eq!(info.tokens.limit, None);
+    assert_eq!(info.input_tokens, RateLimit::default());
+    assert_eq!(info.exhausted_reset_in(), Some(Duration::ZERO));
+}
+
+#[tokio::test(start_paused = true)]
+async fn limiter_refills_at_the_configured_rate() {
+    let limiter = RateLimiter::new(2, Duration::from_secs(60));
+    let start = Instant::now();
+
+    limiter.acquire().await;
+    limiter.acquire().await;
+    assert_eq!(start.elapsed(), Duration::ZERO);
+
+    limiter.acquire().await;
+    assert!(start.elapsed() >= Duration::from_secs(30));
+}
+
+#[tokio::test(start_paused = true)]
+async fn limiter_waits_for_exhausted_limits_to_reset() {
+    let limiter = RateLimiter::per_minute(50);
+    let start = Instant::now();
+
+    limiter.update(&exhausted_requests(Utc::now() + chrono::Duration::seconds(10)));
+    limiter.acquire().await;
+
+    assert!(start.elapsed() >= Duration::from_secs(9));
+}
+
+#[tokio::test]
+async fn client_records_rate_limits_and_throttles_requests() {
+    let server = MockServer::start().await;
+    let reset = Utc::now() + chrono::Duration::milliseconds(300);
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("anthropic-ratelimit-requests-limit", "50")
+                .insert_header("anthropic-ratelimit-requests-remaining", "0")
+                .insert_header("anthropic-ratelimit-requests-reset", rfc3339(reset).as_str())
+                .set_body_json(json!({
+                    "id": "msg_01",
+                    "type": "message",
+                    "role": "assistant",
+                    "content": [{"type": "text", "text": "Hi"}],
+                    "model": "claude-3-haiku-20240307",
+                    "stop_reason": "end_turn",
+                    "stop_sequence": null,
+                    "usage": {"input_tokens": 1, "output_tokens": 1}
+                })),
+        )
+        .expect(2)
+        .mount(&server)
+        .await;
+    let client = ClientBuilder::default()
+        .api_key("test-key".to_string())
+        .api_base(server.uri())
+        .rate_limiter(RateLimiter::per_minute(50))
+        .build()
+        .unwrap();
+    let request = CreateMessageRequestBuilder::default()
+        .model("claude-3-haiku-20240307")
+        .messages(vec![InputMessage::user("Hello")])
+        .max_tokens(16)
+        .build()
+        .unwrap();
+
+    assert_eq!(client.rate_limit_info(), None);
+    client.create_message(request.clone()).await.unwrap();
+    assert_eq!(client.rate_limit_info().unwrap().requests.remaining, Some(0));
+
+    client.create_message(request).await.unwrap();
+    assert!(Utc::now() >= reset - chrono::Duration::milliseconds(20));
+}
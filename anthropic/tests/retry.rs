@@ -0,0 +1,209 @@
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anthropic::error::{AnthropicError, ApiErrorKind};
+use anthropic::retry::{DefaultRetryPolicy, RetryPolicy, retry_after};
+use anthropic::types::CreateMessageRequest;
+use common::{client, client_builder, message_response, request};
+use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, HeaderValue};
+use serde_json::json;
+use tokio_stream::StreamExt;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn stream_request() -> CreateMessageRequest {
+    CreateMessageRequest { stream: true, ..request() }
+}
+
+fn error_response(status: u16, r#type: &str) -> ResponseTemplate {
+    ResponseTemplate::new(status)
+        .set_body_json(json!({"type": "error", "error": {"type": r#type, "message": "try again"}}))
+}
+
+async fn mount_failures(server: &MockServer, response: ResponseTemplate, times: u64) {
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(response)
+        .up_to_n_times(times)
+        .expect(times)
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn overloaded_and_server_errors_are_retried() {
+    let server = MockServer::start().await;
+    mount_failures(&server, error_response(529, "overloaded_error"), 1).await;
+    mount_failures(&server, error_response(503, "api_error"), 1).await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(message_response()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let response = client(&server).create_message(request()).await.unwrap();
+
+    assert_eq!(response.id, "msg_01");
+}
+
+#[tokio::test]
+async fn rate_limited_requests_honour_retry_after() {
+    let server = MockServer::start().await;
+    mount_failures(&server, error_response(429, "rate_limit_error").insert_header("retry-after", "0"), 1).await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(message_response()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    assert!(client(&server).create_message(request()).await.is_ok());
+}
+
+#[tokio::test]
+async fn invalid_requests_are_not_retried() {
+    let server = MockServer::start().await;
+    mount_failures(&server, error_response(400, "invalid_request_error"), 1).await;
+
+    let result = client(&server).create_message(request()).await;
+
+    assert!(matches!(result, Err(AnthropicError::ApiError(error)) if error.kind == ApiErrorKind::InvalidRequestError));
+}
+
+#[tokio::test]
+async fn retries_stop_after_max_attempts() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(error_response(500, "api_error"))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let result = client_builder(&server.uri())
+        .retry_policy(Arc::new(DefaultRetryPolicy { max_attempts: 2, ..Default::default() }))
+        .build()
+        .unwrap()
+        .create_message(request())
+        .await;
+
+    assert!(matches!(result, Err(AnthropicError::ApiError(error)) if error.kind == ApiErrorKind::ApiError));
+}
+
+#[derive(Debug)]
+struct NeverRetry;
+
+impl RetryPolicy for NeverRetry {
+    fn max_attempts(&self) -> u32 {
+        1
+    }
+
+    fn should_retry_status(&self, _status: StatusCode, _headers: &HeaderMap) -> bool {
+        false
+    }
+
+    fn should_retry_error(&self, _error: &AnthropicError) -> bool {
+        false
+    }
+
+    fn retry_after(&self, _headers: &HeaderMap) -> Option<Duration> {
+        None
+    }
+}
+
+#[tokio::test]
+async fn retry_policy_is_pluggable() {
+    let server = MockServer::start().await;
+    mount_failures(&server, error_response(529, "overloaded_error"), 1).await;
+
+    let result = client_builder(&server.uri())
+        .retry_policy(Arc::new(NeverRetry))
+        .build()
+        .unwrap()
+        .create_message(request())
+        .await;
+
+    assert!(matches!(result, Err(AnthropicError::ApiError(error)) if error.kind == ApiErrorKind::OverloadedError));
+}
+
+#[test]
+fn retry_after_is_parsed_from_headers() {
+    let mut headers = HeaderMap::new();
+    assert_eq!(retry_after(&headers), None);
+
+    headers.insert("anthropic-ratelimit-requests-remaining", HeaderValue::from_static("0"));
+    headers.insert("anthropic-ratelimit-requests-reset", HeaderValue::from_static("2000-01-01T00:00:00Z"));
+    assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+
+    headers.insert("retry-after", HeaderValue::from_static("2"));
+    assert_eq!(retry_after(&headers), Some(Duration::from_secs(2)));
+
+    headers.insert("retry-after-ms", HeaderValue::from_static("1500"));
+    assert_eq!(retry_after(&headers), Some(Duration::from_millis(1500)));
+
+    let policy = DefaultRetryPolicy { max_retry_after: Duration::from_secs(1), ..Default::default() };
+    assert_eq!(policy.retry_after(&headers), None);
+    assert!(policy.should_retry_status(StatusCode::CONFLICT, &headers));
+    headers.insert("x-should-retry", HeaderValue::from_static("false"));
+    assert!(!policy.should_retry_status(StatusCode::SERVICE_UNAVAILABLE, &headers));
+}
+
+#[tokio::test]
+async fn streams_are_retried_before_the_first_event() {
+    let server = MockServer::start().await;
+    mount_failures(&server, error_response(529, "overloaded_error"), 1).await;
+    mount_failures(&server, error_response(429, "rate_limit_error").insert_header("retry-after-ms", "1"), 1).await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw(include_str!("fixtures/message_stream.sse"), "text/event-stream"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let stream = client(&server).create_message_stream(stream_request()).await.unwrap();
+    let events: Vec<_> = stream.collect().await;
+
+    assert_eq!(events.len(), 7);
+    assert!(events.iter().all(Result::is_ok));
+}
+
+#[tokio::test]
+async fn exhausted_stream_retries_report_the_failed_attempt() {
+    let server = MockServer::start().await;
+    mount_failures(&server, error_response(529, "overloaded_error"), 2).await;
+
+    let client = client_builder(&server.uri())
+        .retry_policy(Arc::new(DefaultRetryPolicy { max_attempts: 2, ..Default::default() }))
+        .build()
+        .unwrap();
+    let events: Vec<_> = client.create_message_stream(stream_request()).await.unwrap().collect().await;
+
+    assert_eq!(events.len(), 1);
+    match &events[0] {
+        Err(AnthropicError::StreamRetriesExhausted { attempt: 2, source }) => {
+            assert!(
+                matches!(source.as_ref(), AnthropicError::ApiError(error) if error.kind == ApiErrorKind::OverloadedError)
+            )
+        }
+        other => panic!("unexpected stream item: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn stream_connection_failures_are_retried() {
+    // Nothing listens on the discard port.
+    let client = client_builder("http://127.0.0.1:9").build().unwrap();
+    let events: Vec<_> = client.create_message_stream(stream_request()).await.unwrap().collect().await;
+
+    assert_eq!(events.len(), 1);
+    assert!(matches!(
+        &events[0],
+        Err(AnthropicError::StreamRetriesExhausted { attempt: 3, source }) if matches!(**source, AnthropicError::Reqwest(_))
+    ));
+}
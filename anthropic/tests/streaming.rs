@@ -0,0 +1,210 @@
+mod common;
+
+use anthropic::error::{AnthropicError, ApiErrorKind};
+use anthropic::stream::MessageAccumulator;
+use anthropic::types::{
+    CompleteRequestBuilder, ContentBlock, ContentDelta, CreateMessageRequest, CreateMessageResponse,
+    CreateMessageResponseStream, MessageDeltaUsage, StopReason, StreamEvent,
+};
+use common::{client, request};
+use serde_json::json;
+use tokio_stream::StreamExt;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn sse_server(endpoint: &str, transcript: &'static str) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(endpoint))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(transcript, "text/event-stream"))
+        .mount(&server)
+        .await;
+    server
+}
+
+async fn message_stream(server: &MockServer) -> CreateMessageResponseStream {
+    let request = CreateMessageRequest { stream: true, ..request() };
+    client(server).create_message_stream(request).await.unwrap()
+}
+
+async fn message_events(server: &MockServer) -> Vec<Result<StreamEvent, AnthropicError>> {
+    message_stream(server).await.collect().await
+}
+
+#[tokio::test]
+async fn message_stream_yields_events_until_message_stop() {
+    let server = sse_server("/v1/messages", include_str!("fixtures/message_stream.sse")).await;
+
+    let events: Vec<StreamEvent> = message_events(&server).await.into_iter().map(Result::unwrap).collect();
+
+    assert_eq!(events.len(), 7);
+    assert!(matches!(&events[0], StreamEvent::MessageStart { message } if message.id == "msg_01"));
+    assert!(matches!(events[1], StreamEvent::ContentBlockStart { index: 0, .. }));
+    assert_eq!(
+        events[2],
+        StreamEvent::ContentBlockDelta { index: 0, delta: ContentDelta::TextDelta { text: "Dogs have".to_string() } }
+    );
+    assert!(matches!(events[3], StreamEvent::ContentBlockDelta { index: 0, .. }));
+    assert_eq!(events[4], StreamEvent::ContentBlockStop { index: 0 });
+    match &events[5] {
+        StreamEvent::MessageDelta { delta, usage } => {
+            assert_eq!(delta.stop_reason, Some(StopReason::EndTurn));
+            assert_eq!(usage, &Some(MessageDeltaUsage { output_tokens: 8 }));
+        }
+        other => panic!("expected a message delta, got {other:?}"),
+    }
+    assert_eq!(events[6], StreamEvent::MessageStop);
+}
+
+#[tokio::test]
+async fn message_stream_is_sent_once_polled() {
+    let server = sse_server("/v1/messages", include_str!("fixtures/message_stream.sse")).await;
+
+    let mut stream = message_stream(&server).await;
+    assert!(server.received_requests().await.unwrap().is_empty());
+
+    assert!(matches!(stream.next().await, Some(Ok(StreamEvent::MessageStart { .. }))));
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn message_stream_surfaces_error_events() {
+    let server = sse_server("/v1/messages", include_str!("fixtures/message_stream_error.sse")).await;
+
+    let events = message_events(&server).await;
+
+    assert_eq!(events.len(), 2);
+    assert!(matches!(events[0], Ok(StreamEvent::MessageStart { .. })));
+    match &events[1] {
+        Err(AnthropicError::ApiError(error)) => {
+            assert_eq!(error.kind, ApiErrorKind::OverloadedError);
+            assert_eq!(error.message, "Overloaded");
+            assert_eq!(error.status, None);
+            assert!(error.is_retryable());
+        }
+        other => panic!("expected an API error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn message_stream_surfaces_error_responses() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+            "type": "error",
+            "error": {"type": "authentication_error", "message": "invalid x-api-key"}
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let events = message_events(&server).await;
+
+    assert_eq!(events.len(), 1);
+    assert!(
+        matches!(&events[0], Err(AnthropicError::ApiError(error)) if error.kind == ApiErrorKind::AuthenticationError)
+    );
+}
+
+#[tokio::test]
+async fn completion_stream_yields_completion_events() {
+    let server = sse_server("/v1/complete", include_str!("fixtures/completion_stream.sse")).await;
+    let request = CompleteRequestBuilder::default()
+        .prompt("\n\nHuman: Hi\n\nAssistant:")
+        .max_tokens_to_sample(16usize)
+        .stream(true)
+        .build()
+        .unwrap();
+
+    let responses: Vec<_> = client(&server).complete_stream(request).await.unwrap().collect().await;
+
+    assert_eq!(responses.len(), 2);
+    let last = responses[1].as_ref().unwrap();
+    assert_eq!(last.completion, "!");
+    assert_eq!(last.stop_reason, Some(StopReason::StopSequence));
+}
+
+#[tokio::test]
+async fn accumulated_stream_matches_non_streaming_response() {
+    let server = sse_server("/v1/messages", include_str!("fixtures/message_stream.sse")).await;
+
+    let message = MessageAccumulator::collect(message_stream(&server).await).await.unwrap();
+
+    let expected: CreateMessageResponse = serde_json::from_value(json!({
+        "id": "msg_01",
+        "type": "message",
+        "role": "assistant",
+        "content": [{"type": "text", "text": "Dogs have 18 toes."}],
+        "model": "claude-3-haiku-20240307",
+        "stop_reason": "end_turn",
+        "stop_sequence": null,
+        "usage": {"input_tokens": 12, "output_tokens": 8}
+    }))
+    .unwrap();
+    assert_eq!(message, expected);
+}
+
+#[tokio::test]
+async fn accumulator_exposes_partial_message() {
+    let server = sse_server("/v1/messages", include_str!("fixtures/message_stream.sse")).await;
+    let mut stream = message_stream(&server).await;
+    let mut accumulator = MessageAccumulator::new();
+
+    for _ in 0..3 {
+        accumulator.push(&stream.next().await.unwrap().unwrap()).unwrap();
+    }
+
+    let partial = accumulator.message().unwrap();
+    assert_eq!(partial.content[0].as_text(), Some("Dogs have"));
+    assert_eq!(partial.stop_reason, None);
+    assert!(!accumulator.is_complete());
+    assert!(matches!(accumulator.finish(), Err(AnthropicError::StreamError(_))));
+}
+
+#[tokio::test]
+async fn accumulator_builds_thinking_blocks() {
+    let server = sse_server("/v1/messages", include_str!("fixtures/thinking_stream.sse")).await;
+
+    let message = MessageAccumulator::collect(message_stream(&server).await).await.unwrap();
+
+    assert_eq!(
+        message.content[0],
+        ContentBlock::Thinking {
+            thinking: "Dogs have 4 paws of 4 or 5 toes.".to_string(),
+            signature: "c2ln".to_string()
+        }
+    );
+    assert_eq!(message.content[1].as_text(), Some("Dogs have 18 toes."));
+}
+
+#[test]
+fn accumulator_indexes_blocks_after_the_content_of_message_start() {
+    let events: Vec<StreamEvent> = serde_json::from_value(json!([
+        {"type": "message_start", "message": {
+            "id": "msg_01", "type": "message", "role": "assistant", "model": "claude-3-haiku-20240307",
+            "content": [{"type": "text", "text": "Let me check."}],
+            "stop_reason": null, "stop_sequence": null, "usage": {"input_tokens": 12, "output_tokens": 1}
+        }},
+        {"type": "content_block_start", "index": 1, "content_block": {
+            "type": "tool_use", "id": "toolu_01", "name": "get_weather", "input": {}
+        }},
+        {"type": "content_block_delta", "index": 1, "delta": {"type": "input_json_delta", "partial_json": "{\"location\":"}},
+        {"type": "content_block_delta", "index": 1, "delta": {"type": "input_json_delta", "partial_json": " \"Paris\"}"}},
+        {"type": "content_block_stop", "index": 1}
+    ]))
+    .unwrap();
+    let mut accumulator = MessageAccumulator::new();
+
+    for event in &events {
+        accumulator.push(event).unwrap();
+    }
+
+    let message = accumulator.message().unwrap();
+    assert_eq!(message.content[0].as_text(), Some("Let me check."));
+    assert!(
+        matches!(&message.content[1], ContentBlock::ToolUse { input, .. } if *input == json!({"location": "Paris"}))
+    );
+    let stop = StreamEvent::ContentBlockStop { index: 2 };
+    assert!(matches!(accumulator.push(&stop), Err(AnthropicError::StreamError(_))));
+}
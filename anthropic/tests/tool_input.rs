@@ -0,0 +1,114 @@
+use anthropic::error::AnthropicError;
+use anthropic::tools::{InputSchema, ToolInput};
+use serde::Deserialize;
+use serde_json::json;
+
+/// A temperature unit.
+#[derive(Debug, Deserialize, PartialEq, InputSchema)]
+struct Unit {
+    /// The symbol of the unit.
+    symbol: String,
+}
+
+/// Get the current weather in a given location.
+#[derive(Debug, Deserialize, PartialEq, ToolInput)]
+struct GetWeather {
+    /// The city and country, e.g. Paris, France.
+    location: String,
+    /// Number of forecast days.
+    days: Option<u8>,
+    #[serde(rename = "temperature_unit", default)]
+    unit: Vec<Unit>,
+}
+
+/// Search the documentation.
+#[derive(Debug, Deserialize, ToolInput)]
+#[tool(name = "docs_search")]
+struct Search {
+    query: String,
+}
+
+/// Send an HTTP request.
+#[derive(Debug, Default, Deserialize, ToolInput)]
+#[serde(rename_all = "camelCase", default)]
+struct HTTPRequest {
+    target_url: String,
+    #[serde(rename(serialize = "method", deserialize = "verb"))]
+    http_method: String,
+}
+
+/// Process the items.
+#[derive(Debug, Deserialize, ToolInput)]
+struct ProcessItems<T> {
+    items: Vec<T>,
+}
+
+#[test]
+fn derived_tool_definition() {
+    let tool = GetWeather::tool();
+
+    assert_eq!(tool.name, "get_weather");
+    assert_eq!(tool.description.as_deref(), Some("Get the current weather in a given location."));
+    assert_eq!(
+        tool.input_schema,
+        json!({
+            "type": "object",
+            "properties": {
+                "location": {"type": "string", "description": "The city and country, e.g. Paris, France."},
+                "days": {"type": "integer", "description": "Number of forecast days."},
+                "temperature_unit": {"type": "array", "items": {
+                    "type": "object",
+                    "properties": {"symbol": {"type": "string", "description": "The symbol of the unit."}},
+                    "required": ["symbol"]
+                }}
+            },
+            "required": ["location"]
+        })
+    );
+    assert_eq!(Search::tool().name, "docs_search");
+    assert_eq!(Search::input_schema()["required"], json!(["query"]));
+    assert_eq!(Search::from_input(json!({"query": "tools"})).unwrap().query, "tools");
+}
+
+#[test]
+fn tool_input_is_deserialized() {
+    let input = GetWeather::from_input(json!({"location": "Paris", "temperature_unit": [{"symbol": "C"}]})).unwrap();
+
+    assert_eq!(
+        input,
+        GetWeather { location: "Paris".to_string(), days: None, unit: vec![Unit { symbol: "C".to_string() }] }
+    );
+}
+
+#[test]
+fn invalid_tool_input_is_reported() {
+    let result = GetWeather::from_input(json!({"days": 3}));
+
+    assert!(matches!(result, Err(AnthropicError::InvalidToolInput { tool, .. }) if tool == "get_weather"));
+}
+
+#[test]
+fn serde_container_attributes_are_applied() {
+    let tool = HTTPRequest::tool();
+
+    assert_eq!(tool.name, "http_request");
+    assert_eq!(
+        tool.input_schema,
+        json!({
+            "type": "object",
+            "properties": {"targetUrl": {"type": "string"}, "verb": {"type": "string"}}
+        })
+    );
+    let input = HTTPRequest::from_input(json!({"targetUrl": "https://example.com", "verb": "GET"})).unwrap();
+    assert_eq!((input.target_url.as_str(), input.http_method.as_str()), ("https://example.com", "GET"));
+}
+
+#[test]
+fn generic_tool_inputs_use_the_schema_of_their_parameters() {
+    let tool = ProcessItems::<Unit>::tool();
+
+    assert_eq!(tool.name, "process_items");
+    assert_eq!(tool.input_schema["properties"]["items"]["items"], Unit::input_schema());
+    let input = ProcessItems::<Unit>::from_input(json!({"items": [{"symbol": "K"}]})).unwrap();
+    assert_eq!(input.items, vec![Unit { symbol: "K".to_string() }]);
+}
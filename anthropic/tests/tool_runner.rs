@@ -0,0 +1,133 @@
+mod common;
+
+use anthropic::error::AnthropicError;
+use anthropic::tools::ToolRunner;
+use anthropic::types::{ContentBlock, InputMessage, Tool, ToolBuilder};
+use common::{client, request};
+use serde_json::{Value, json};
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn weather_tool() -> Tool {
+    ToolBuilder::default()
+        .name("get_weather")
+        .input_schema(json!({"type": "object", "properties": {"location": {"type": "string"}}}))
+        .build()
+        .unwrap()
+}
+
+fn response(stop_reason: &str, content: Value) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(json!({
+        "id": "msg_01",
+        "type": "message",
+        "role": "assistant",
+        "content": content,
+        "model": "claude-3-haiku-20240307",
+        "stop_reason": stop_reason,
+        "stop_sequence": null,
+        "usage": {"input_tokens": 10, "output_tokens": 10}
+    }))
+}
+
+fn tool_use_response() -> ResponseTemplate {
+    response(
+        "tool_use",
+        json!([{"type": "tool_use", "id": "toolu_01", "name": "get_weather", "input": {"location": "Paris"}}]),
+    )
+}
+
+#[tokio::test]
+async fn runner_executes_tools_until_end_turn() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(body_partial_json(json!({"tools": [{"name": "get_weather"}]})))
+        .respond_with(tool_use_response())
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(body_partial_json(json!({"messages": [{}, {}, {"role": "user", "content": [
+            {"type": "tool_result", "tool_use_id": "toolu_01", "content": "Sunny in Paris"}
+        ]}]})))
+        .respond_with(response("end_turn", json!([{"type": "text", "text": "It is sunny."}])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let runner = ToolRunner::new().register(weather_tool(), |input| async move {
+        Ok::<_, String>(format!("Sunny in {}", input["location"].as_str().unwrap()))
+    });
+    let output = runner.run(&client(&server), request()).await.unwrap();
+
+    assert_eq!(output.response.content, vec![ContentBlock::text("It is sunny.")]);
+    assert_eq!(output.messages.len(), 4);
+    assert_eq!(output.messages[3], InputMessage::assistant(vec![ContentBlock::text("It is sunny.")]));
+}
+
+#[tokio::test]
+async fn handler_errors_are_sent_as_error_results() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(tool_use_response())
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(body_partial_json(json!({"messages": [{}, {}, {"role": "user", "content": [
+            {"type": "tool_result", "tool_use_id": "toolu_01", "content": "service unavailable", "is_error": true}
+        ]}]})))
+        .respond_with(response("end_turn", json!([{"type": "text", "text": "Sorry."}])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let runner =
+        ToolRunner::new().register(weather_tool(), |_| async { Err::<String, _>("service unavailable".to_string()) });
+    let output = runner.run(&client(&server), request()).await.unwrap();
+
+    assert_eq!(output.response.content, vec![ContentBlock::text("Sorry.")]);
+}
+
+#[tokio::test]
+async fn runner_stops_after_max_iterations() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(tool_use_response())
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let runner = ToolRunner::new().max_iterations(2).register(weather_tool(), |_| async { Ok::<_, String>("Sunny") });
+    let result = runner.run(&client(&server), request()).await;
+
+    let Err(AnthropicError::MaxIterations { iterations, messages }) = result else {
+        panic!("expected MaxIterations, got {result:?}");
+    };
+    assert_eq!(iterations, 2);
+    // The transcript ends with the results of the tools requested by the last response.
+    assert_eq!(messages.len(), 5);
+    assert_eq!(messages[4], InputMessage::user(vec![ContentBlock::tool_result("toolu_01", "Sunny")]));
+}
+
+#[tokio::test]
+async fn tool_use_without_tool_use_blocks_ends_the_run() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(response("tool_use", json!([{"type": "text", "text": "Let me check."}])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let runner = ToolRunner::new().register(weather_tool(), |_| async { Ok::<_, String>("Sunny") });
+    let output = runner.run(&client(&server), request()).await.unwrap();
+
+    assert_eq!(output.response.content, vec![ContentBlock::text("Let me check.")]);
+    assert_eq!(output.messages.len(), 2);
+}
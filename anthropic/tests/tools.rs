@@ -0,0 +1,130 @@
+mod common;
+
+use anthropic::stream::MessageAccumulator;
+use anthropic::types::{ContentBlock, CreateMessageRequestBuilder, InputMessage, StopReason, ToolBuilder, ToolChoice};
+use common::client;
+use serde_json::json;
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn weather_tool() -> anthropic::types::Tool {
+    ToolBuilder::default()
+        .name("get_weather")
+        .description("Get the current weather in a given location")
+        .input_schema(json!({
+            "type": "object",
+            "properties": {"location": {"type": "string"}},
+            "required": ["location"]
+        }))
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn tools_are_sent_and_tool_use_blocks_are_parsed() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .and(body_partial_json(json!({
+            "tools": [{
+                "name": "get_weather",
+                "description": "Get the current weather in a given location",
+                "input_schema": {
+                    "type": "object",
+                    "properties": {"location": {"type": "string"}},
+                    "required": ["location"]
+                }
+            }],
+            "tool_choice": {"type": "tool", "name": "get_weather"},
+            "messages": [
+                {"role": "user", "content": "What's the weather in Paris?"},
+                {"role": "assistant", "content": [
+                    {"type": "tool_use", "id": "toolu_00", "name": "get_weather", "input": {"location": "Paris"}}
+                ]},
+                {"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": "toolu_00", "content": "unavailable", "is_error": true}
+                ]}
+            ]
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "msg_02",
+            "type": "message",
+            "role": "assistant",
+            "content": [
+                {"type": "text", "text": "Let me check again."},
+                {"type": "tool_use", "id": "toolu_01", "name": "get_weather", "input": {"location": "Paris"}}
+            ],
+            "model": "claude-3-haiku-20240307",
+            "stop_reason": "tool_use",
+            "stop_sequence": null,
+            "usage": {"input_tokens": 40, "output_tokens": 25}
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let request = CreateMessageRequestBuilder::default()
+        .model("claude-3-haiku-20240307")
+        .messages(vec![
+            InputMessage::user("What's the weather in Paris?"),
+            InputMessage::assistant(vec![ContentBlock::ToolUse {
+                id: "toolu_00".to_string(),
+                name: "get_weather".to_string(),
+                input: json!({"location": "Paris"}),
+            }]),
+            InputMessage::user(vec![ContentBlock::tool_error("toolu_00", "unavailable")]),
+        ])
+        .max_tokens(256)
+        .tools(vec![weather_tool()])
+        .tool_choice(ToolChoice::Tool { name: "get_weather".to_string() })
+        .build()
+        .unwrap();
+
+    let response = client(&server).create_message(request).await.unwrap();
+
+    assert_eq!(
+        response.content[1],
+        ContentBlock::ToolUse {
+            id: "toolu_01".to_string(),
+            name: "get_weather".to_string(),
+            input: json!({"location": "Paris"}),
+        }
+    );
+}
+
+#[tokio::test]
+async fn streamed_tool_input_is_assembled_from_json_deltas() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/messages"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw(include_str!("fixtures/tool_use_stream.sse"), "text/event-stream"),
+        )
+        .mount(&server)
+        .await;
+
+    let request = CreateMessageRequestBuilder::default()
+        .model("claude-3-haiku-20240307")
+        .messages(vec![InputMessage::user("What's the weather in Paris?")])
+        .max_tokens(256)
+        .tools(vec![weather_tool()])
+        .stream(true)
+        .build()
+        .unwrap();
+
+    let stream = client(&server).create_message_stream(request).await.unwrap();
+    let message = MessageAccumulator::collect(stream).await.unwrap();
+
+    assert_eq!(
+        message.content,
+        vec![
+            ContentBlock::text("Let me check."),
+            ContentBlock::ToolUse {
+                id: "toolu_01".to_string(),
+                name: "get_weather".to_string(),
+                input: json!({"location": "Paris"}),
+            },
+        ]
+    );
+    assert_eq!(message.stop_reason, Some(StopReason::ToolUse));
+}
@@ -0,0 +1,157 @@
+mod common;
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use anthropic::client::Client;
+use anthropic::error::AnthropicError;
+use anthropic::transport::{HttpResponse, Transport, TransportFuture};
+use anthropic::types::{CreateMessageRequest, StreamEvent};
+use common::{client_builder, request};
+use reqwest::StatusCode;
+use reqwest::header::HeaderMap;
+use serde_json::json;
+use tokio_stream::StreamExt;
+
+/// A request received by the [FixtureTransport].
+#[derive(Debug)]
+struct SentRequest {
+    url: String,
+    accept: Option<String>,
+    content_type: Option<String>,
+    raw_body: Vec<u8>,
+    body: serde_json::Value,
+}
+
+/// Answers the requests with recorded responses, in order.
+#[derive(Debug, Default)]
+struct FixtureTransport {
+    responses: Mutex<VecDeque<Result<(StatusCode, &'static str), AnthropicError>>>,
+    requests: Mutex<Vec<SentRequest>>,
+}
+
+impl FixtureTransport {
+    fn new(responses: Vec<Result<(StatusCode, &'static str), AnthropicError>>) -> Arc<Self> {
+        Arc::new(Self { responses: Mutex::new(responses.into()), requests: Mutex::default() })
+    }
+}
+
+impl Transport for FixtureTransport {
+    fn send(&self, request: reqwest::Request) -> TransportFuture<'_> {
+        let body = request.body().and_then(|body| body.as_bytes()).unwrap_or_default();
+        self.requests.lock().unwrap().push(SentRequest {
+            url: request.url().to_string(),
+            accept: request.headers().get("accept").map(|value| value.to_str().unwrap().to_string()),
+            content_type: request.headers().get("content-type").map(|value| value.to_str().unwrap().to_string()),
+            raw_body: body.to_vec(),
+            body: serde_json::from_slice(body).unwrap_or_default(),
+        });
+        let response = self.responses.lock().unwrap().pop_front().expect("no response left");
+
+        Box::pin(async move {
+            let (status, body) = response?;
+            Ok(HttpResponse::new(status, HeaderMap::new(), body))
+        })
+    }
+}
+
+fn client(transport: Arc<FixtureTransport>) -> Client {
+    client_builder("https://api.anthropic.test").transport(transport).build().unwrap()
+}
+
+const MESSAGE: &str = r#"{
+    "id": "msg_01",
+    "type": "message",
+    "role": "assistant",
+    "content": [{"type": "text", "text": "Dogs have 18 toes."}],
+    "model": "claude-3-haiku-20240307",
+    "stop_reason": "end_turn",
+    "stop_sequence": null,
+    "usage": {"input_tokens": 12, "output_tokens": 8}
+}"#;
+
+const OVERLOADED: &str = r#"{"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}"#;
+
+#[tokio::test]
+async fn requests_are_sent_through_the_transport_and_retried() {
+    let transport = FixtureTransport::new(vec![
+        Ok((StatusCode::from_u16(529).unwrap(), OVERLOADED)),
+        Ok((StatusCode::OK, MESSAGE)),
+    ]);
+
+    let response = client(transport.clone()).create_message(request()).await.unwrap();
+
+    assert_eq!(response.id, "msg_01");
+    let requests = transport.requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].url, "https://api.anthropic.test/v1/messages");
+    assert_eq!(requests[1].body["messages"], json!([{"role": "user", "content": "How many toes do dogs have?"}]));
+}
+
+fn transport_error(message: &str, retryable: bool) -> AnthropicError {
+    AnthropicError::Transport { source: message.into(), retryable }
+}
+
+#[tokio::test]
+async fn retryable_transport_errors_are_retried() {
+    let transport = FixtureTransport::new(vec![
+        Err(transport_error("connection reset", true)),
+        Ok((StatusCode::OK, MESSAGE)),
+        Err(transport_error("invalid certificate", false)),
+    ]);
+    let client = client(transport.clone());
+
+    assert_eq!(client.create_message(request()).await.unwrap().id, "msg_01");
+    assert_eq!(transport.requests.lock().unwrap().len(), 2);
+
+    let result = client.create_message(request()).await;
+    assert!(matches!(result, Err(AnthropicError::Transport { retryable: false, .. })));
+    assert_eq!(transport.requests.lock().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn event_streams_are_parsed_from_the_transport_response() {
+    let transport = FixtureTransport::new(vec![
+        Ok((StatusCode::from_u16(529).unwrap(), OVERLOADED)),
+        Ok((StatusCode::OK, include_str!("fixtures/message_stream.sse"))),
+    ]);
+    let request = CreateMessageRequest { stream: true, ..request() };
+
+    let events: Vec<StreamEvent> =
+        client(transport.clone()).create_message_stream(request).await.unwrap().map(Result::unwrap).collect().await;
+
+    assert_eq!(events.len(), 7);
+    assert_eq!(events[6], StreamEvent::MessageStop);
+    let requests = transport.requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].accept.as_deref(), Some("text/event-stream"));
+}
+
+#[tokio::test]
+async fn file_uploads_are_sent_through_the_transport() {
+    let file = r#"{
+        "type": "file",
+        "id": "file_1",
+        "filename": "notes.txt",
+        "mime_type": "text/plain",
+        "size_bytes": 11,
+        "created_at": "2025-04-14T00:00:00Z"
+    }"#;
+    let transport = FixtureTransport::new(vec![Ok((StatusCode::OK, file))]);
+    let client = client(transport.clone());
+
+    let uploaded = client.upload_file_bytes("notes.txt", "text/plain", b"hello world".to_vec()).await.unwrap();
+
+    assert_eq!(uploaded.id, "file_1");
+    let sent = transport.requests.lock().unwrap().remove(0);
+    assert_eq!(sent.url, "https://api.anthropic.test/v1/files");
+    let boundary = sent.content_type.as_deref().unwrap().strip_prefix("multipart/form-data; boundary=").unwrap();
+    let body = String::from_utf8(sent.raw_body.clone()).unwrap();
+    assert!(body.starts_with(&format!("--{boundary}\r\n")));
+    assert!(body.contains("filename=\"notes.txt\"\r\nContent-Type: text/plain\r\n\r\nhello world\r\n"));
+    assert!(body.ends_with(&format!("--{boundary}--\r\n")));
+
+    let reader = std::io::Cursor::new(b"hello world".to_vec());
+    let result = client.upload_file_reader("notes.txt", "text/plain", reader).await;
+    assert!(matches!(result, Err(AnthropicError::InvalidArgument(_))));
+}
@@ -3,8 +3,8 @@ use std::io::Write;
 
 use anthropic::client::Client;
 use anthropic::config::AnthropicConfig;
-use anthropic::types::{CreateMessageRequestBuilder, StopReason};
-use anthropic::{AI_PROMPT, HUMAN_PROMPT};
+use anthropic::stream::MessageAccumulator;
+use anthropic::types::{ContentDelta, CreateMessageRequestBuilder, InputMessage, StreamEvent};
 use dotenv::dotenv;
 use tokio_stream::StreamExt;
 
@@ -20,28 +20,25 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let cfg = AnthropicConfig::new()?;
     let client = Client::try_from(cfg)?;
 
-    let complete_request = CreateMessageRequestBuilder::default()
-        //.prompt(format!("{HUMAN_PROMPT}How many toes do dogs have?{AI_PROMPT}"))
-        .model("claude-2".to_string())
-        //.max_tokens_to_sample(256usize)
+    let message_request = CreateMessageRequestBuilder::default()
+        .model("claude-3-haiku-20240307".to_string())
+        .messages(vec![InputMessage::user("How many toes do dogs have?")])
+        .max_tokens(256)
         .stream(true)
-        .stop_sequences(vec![HUMAN_PROMPT.to_string()])
         .build()?;
 
-    // Send a completion request.
-    let mut stream = client.create_message_stream(complete_request).await?;
+    // Send a message request.
+    let mut stream = client.create_message_stream(message_request).await?;
+    let mut accumulator = MessageAccumulator::new();
 
-    while let Some(resp) = stream.next().await {
-        match resp {
-            Ok(response) => {
-                /*
-                if let Some(StopReason::StopSequence) = response.stop_reason {
-                    break;
+    while let Some(event) = stream.next().await {
+        match event {
+            Ok(event) => {
+                if let StreamEvent::ContentBlockDelta { delta: ContentDelta::TextDelta { text }, .. } = &event {
+                    print!("{text}");
+                    std::io::stdout().flush().unwrap();
                 }
-
-                print!("{}", response.completion);
-                std::io::stdout().flush().unwrap();
-                */
+                accumulator.push(&event)?;
             }
             Err(e) => {
                 println!("\n{e}\n")
@@ -49,5 +46,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
         }
     }
 
+    println!("\n\nmessage: {:?}", accumulator.finish()?);
+
     Ok(())
 }